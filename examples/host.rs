@@ -0,0 +1,26 @@
+use bad_lang_2::{
+    Runtime, Tokenizer,
+    token::base::{BaseToken, NullToken, ValueToken},
+};
+
+fn main() {
+    let source = "host#log(\"hello from the script\", 42)\nlet x = host#log(\"x is\", 7)\n";
+
+    let mut tokenizer = Tokenizer::new(source, "host.bl");
+    tokenizer.parse();
+
+    let mut runtime = Runtime::new(tokenizer.tokens.clone());
+    runtime.register_fn("host#log", |args| {
+        let message = args
+            .iter()
+            .map(|arg| arg.value(0))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("[host] {message}");
+
+        ValueToken::Null(NullToken {
+            location: Default::default(),
+        })
+    });
+    runtime.run();
+}
@@ -0,0 +1,15 @@
+pub mod runtime;
+pub mod token;
+
+pub use runtime::Runtime;
+pub use token::Tokenizer;
+
+/// Tokenizes and runs `src`, using `filename` in error locations, and returns
+/// the program's final value.
+pub fn run_source(src: &str, filename: &str) -> Option<token::base::ValueToken> {
+    let mut tokenizer = Tokenizer::new(src, filename);
+    tokenizer.parse();
+
+    let mut runtime = Runtime::new(tokenizer.tokens.clone());
+    runtime.run()
+}
@@ -1,5 +1,4 @@
-mod runtime;
-mod token;
+use bad_lang_2::{runtime, token};
 
 fn main() {
     let file = std::env::args().nth(1).expect("no file provided");
@@ -7,6 +6,28 @@ fn main() {
     let input = std::fs::read_to_string(&file).unwrap();
     let mut tokenizer = token::Tokenizer::new(&input, &file);
 
+    if std::env::args().any(|arg| arg == "--check") {
+        // parse errors are panics, so report just their message and exit with a failure code
+        std::panic::set_hook(Box::new(|info| {
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| payload.downcast_ref::<&str>().copied())
+                .unwrap_or("unknown error");
+
+            eprintln!("error: {message}");
+        }));
+
+        if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| tokenizer.parse())).is_err() {
+            std::process::exit(1);
+        }
+
+        println!("{file}: ok");
+
+        return;
+    }
+
     tokenizer.parse();
 
     if std::env::args().any(|arg| arg.starts_with("--tokens=")) {
@@ -23,5 +44,15 @@ fn main() {
     }
 
     let mut runtime = runtime::Runtime::new(tokenizer.tokens.clone());
+    runtime.set_trace(std::env::args().any(|arg| arg == "--trace"));
+    runtime.set_sandbox(std::env::args().any(|arg| arg == "--sandbox"));
+    runtime.set_debug(std::env::args().any(|arg| arg == "--debug"));
+    runtime.set_profile(std::env::args().any(|arg| arg == "--profile"));
+    runtime.set_max_steps(
+        std::env::args()
+            .find(|arg| arg.starts_with("--max-steps="))
+            .map(|arg| arg[12..].parse().expect("invalid --max-steps value")),
+    );
     runtime.run();
+    runtime.report_profile();
 }
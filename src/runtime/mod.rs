@@ -1,17 +1,22 @@
 use crate::token::{
-    InsideToken, Token,
+    InsideToken, Token, TokenLocation,
     base::{
-        BaseToken, BooleanToken, ClassInstanceToken, NullToken, NumberToken, StringToken,
-        ValueToken,
+        ArrayToken, BaseToken, BooleanToken, ClassInstanceToken, FunctionToken, NamespaceToken,
+        NullToken, NumberToken, Scope, StringToken, ValueToken,
     },
     comparison::ComparisonOperator,
-    logic::{ExpressionToken, LetToken, NumOperation, ReturnToken},
+    logic::{ExpressionToken, InterpolationPart, LetToken, NumOperation, ReturnToken},
     runtime,
 };
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, RwLock};
+use std::io::Write;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// A native function registered by an embedding host, called with evaluated arguments.
+pub type HostFunction = Arc<dyn Fn(&[ValueToken]) -> ValueToken + Send + Sync>;
 
 pub struct Runtime {
     tokens: Vec<Token>,
@@ -20,6 +25,17 @@ pub struct Runtime {
 
     lookup_cache: RefCell<HashMap<String, Arc<RwLock<ExpressionToken>>>>,
     modified_vars: RefCell<HashSet<String>>,
+
+    trace: bool,
+    max_steps: Option<usize>,
+    steps: usize,
+    sandbox: bool,
+    debug: bool,
+    stepping: bool,
+    profile: Option<HashMap<String, (usize, Duration)>>,
+
+    host_functions: HashMap<String, HostFunction>,
+    output: Arc<Mutex<dyn Write + Send>>,
 }
 
 impl Runtime {
@@ -30,15 +46,222 @@ impl Runtime {
             scopes: vec![HashMap::new()],
             lookup_cache: RefCell::new(HashMap::new()),
             modified_vars: RefCell::new(HashSet::new()),
+            trace: false,
+            max_steps: None,
+            steps: 0,
+            sandbox: false,
+            debug: false,
+            stepping: false,
+            profile: None,
+            host_functions: HashMap::new(),
+            output: Arc::new(Mutex::new(std::io::stdout())),
+        }
+    }
+
+    /// Replaces where `io#` builtins print, e.g. with an `Arc<Mutex<Vec<u8>>>` the host
+    /// reads back after running.
+    pub fn set_output(&mut self, output: Arc<Mutex<dyn Write + Send>>) {
+        self.output = output;
+    }
+
+    pub fn output(&self) -> Arc<Mutex<dyn Write + Send>> {
+        Arc::clone(&self.output)
+    }
+
+    /// Registers a native function callable from scripts as `namespace#name(...)`.
+    /// Names outside the built-in namespaces are accepted by the tokenizer.
+    pub fn register_fn(
+        &mut self,
+        name: &str,
+        f: impl Fn(&[ValueToken]) -> ValueToken + Send + Sync + 'static,
+    ) {
+        self.host_functions.insert(name.to_string(), Arc::new(f));
+    }
+
+    pub fn host_function(&self, name: &str) -> Option<HostFunction> {
+        self.host_functions.get(name).cloned()
+    }
+
+    pub fn host_functions(&self) -> HashMap<String, HostFunction> {
+        self.host_functions.clone()
+    }
+
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    pub fn set_sandbox(&mut self, sandbox: bool) {
+        self.sandbox = sandbox;
+    }
+
+    pub fn sandboxed(&self) -> bool {
+        self.sandbox
+    }
+
+    pub fn set_max_steps(&mut self, max_steps: Option<usize>) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn max_steps(&self) -> Option<usize> {
+        self.max_steps
+    }
+
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    pub fn set_profile(&mut self, profile: bool) {
+        self.profile = profile.then(HashMap::new);
+    }
+
+    /// Prints builtin call counts and time spent, per namespace and per function, to stderr.
+    pub fn report_profile(&self) {
+        let Some(profile) = &self.profile else {
+            return;
+        };
+
+        let mut categories: HashMap<&str, (usize, Duration)> = HashMap::new();
+        for (name, (calls, time)) in profile {
+            let category = match name.split_once('#') {
+                Some(("", _)) | None => "logic",
+                Some((namespace, _)) => namespace,
+            };
+
+            let entry = categories.entry(category).or_default();
+            entry.0 += calls;
+            entry.1 += *time;
+        }
+
+        let mut categories = categories.into_iter().collect::<Vec<_>>();
+        categories.sort_by_key(|(_, (_, time))| std::cmp::Reverse(*time));
+
+        let mut functions = profile.iter().collect::<Vec<_>>();
+        functions.sort_by_key(|(_, (_, time))| std::cmp::Reverse(*time));
+
+        eprintln!(
+            "[profile] {:<24} {:>10} {:>14}",
+            "category", "calls", "time"
+        );
+        for (category, (calls, time)) in categories {
+            eprintln!(
+                "[profile] {category:<24} {calls:>10} {:>14}",
+                format!("{time:.3?}")
+            );
+        }
+
+        eprintln!(
+            "[profile] {:<24} {:>10} {:>14}",
+            "function", "calls", "time"
+        );
+        for (name, (calls, time)) in functions {
+            eprintln!(
+                "[profile] {name:<24} {calls:>10} {:>14}",
+                format!("{time:.3?}")
+            );
+        }
+    }
+
+    fn describe_token(token: &Token) -> String {
+        match token {
+            Token::Let(token) => format!("let {}", token.name),
+            Token::LetAssign(token) => format!("assign {}", token.name),
+            Token::LetAssignNum(token) => format!("assign {}", token.name),
+            Token::FnCall(token) => format!("call {} in {}", token.name, token.location),
+            Token::StaticClassFnCall(token) => format!("call {}::{}", token.class, token.name),
+            Token::ClassFnCall(token) => format!("call {}.{}", token.instance, token.name),
+            Token::Loop(_) => "loop".to_string(),
+            Token::Times(_) => "times".to_string(),
+            Token::While(_) => "while".to_string(),
+            Token::Foreach(token) => format!("foreach {}", token.item),
+            Token::Break(_) => "break".to_string(),
+            Token::Return(_) => "return".to_string(),
+            Token::If(_) => "if".to_string(),
         }
     }
 
-    pub fn run(&mut self) {
+    fn trace_token(&self, token: &Token) {
+        eprintln!(
+            "[trace] {}{}",
+            "  ".repeat(self.call_stack.len()),
+            Self::describe_token(token)
+        );
+    }
+
+    /// Pauses at a `#breakpoint()` call when running with `--debug`.
+    pub fn breakpoint(&mut self, location: &TokenLocation) {
+        if self.debug {
+            self.debug_prompt(&format!("breakpoint in {location}"));
+        }
+    }
+
+    fn debug_prompt(&mut self, context: &str) {
+        eprintln!("[debug] {context}");
+
+        loop {
+            eprint!("(debug) ");
+
+            let mut input = String::new();
+            if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+                self.stepping = false;
+                return;
+            }
+
+            let mut parts = input.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("s" | "step"), _) => {
+                    self.stepping = true;
+                    return;
+                }
+                (Some("c" | "continue") | None, _) => {
+                    self.stepping = false;
+                    return;
+                }
+                (Some("l" | "locals"), _) => {
+                    let mut variables = self.scope_aggregate(true).into_iter().collect::<Vec<_>>();
+                    variables.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+                    for (name, value) in variables {
+                        if let Some(value) = self.extract_value(&value.read().unwrap()) {
+                            eprintln!("{name} = {}", value.inspect());
+                        }
+                    }
+                }
+                (Some("p" | "print"), Some(name)) => match self.lookup_variable(name) {
+                    Some(value) => {
+                        if let Some(value) = self.extract_value(&value.read().unwrap()) {
+                            eprintln!("{name} = {}", value.inspect());
+                        }
+                    }
+                    None => eprintln!("variable {name} not found"),
+                },
+                _ => eprintln!("commands: step (s), continue (c), locals (l), print (p) <name>"),
+            }
+        }
+    }
+
+    /// Runs the program, returning the value of the last top-level statement
+    /// or of an explicit top-level `return`.
+    pub fn run(&mut self) -> Option<ValueToken> {
         let tokens_clone = self.tokens.clone();
+        let mut last = None;
 
         for token in tokens_clone {
-            self.execute(&token);
+            match self.execute(&token) {
+                Some(ExpressionToken::Return(return_token)) => {
+                    let value = self.extract_value(&return_token.value);
+                    if let Token::Return(_) = token {
+                        return value;
+                    }
+
+                    // a call whose function returned evaluates to that value
+                    last = value;
+                }
+                Some(value) => last = self.extract_value(&value),
+                None => {}
+            }
         }
+
+        last
     }
 
     fn scope_set(&mut self, name: &str, value: Arc<RwLock<ExpressionToken>>) {
@@ -53,7 +276,7 @@ impl Runtime {
             .insert(name.to_string(), value);
     }
 
-    fn lookup_variable(&self, name: &str) -> Option<Arc<RwLock<ExpressionToken>>> {
+    pub fn lookup_variable(&self, name: &str) -> Option<Arc<RwLock<ExpressionToken>>> {
         if !self.modified_vars.borrow().contains(name)
             && let Some(value) = self.lookup_cache.borrow().get(name)
         {
@@ -105,7 +328,30 @@ impl Runtime {
         self.scopes.push(HashMap::new());
     }
 
+    /// Counts towards `--max-steps`, called per statement and per loop iteration so
+    /// that loops with empty bodies are bounded too.
+    fn count_step(&mut self) {
+        if let Some(max_steps) = self.max_steps {
+            self.steps += 1;
+
+            if self.steps > max_steps {
+                eprintln!("step limit exceeded ({max_steps} steps)");
+                std::process::exit(1);
+            }
+        }
+    }
+
     fn execute(&mut self, token: &Token) -> Option<ExpressionToken> {
+        if self.trace {
+            self.trace_token(token);
+        }
+
+        if self.stepping {
+            self.debug_prompt(&Self::describe_token(token));
+        }
+
+        self.count_step();
+
         match token {
             Token::Let(let_token) => {
                 let value = self
@@ -113,9 +359,10 @@ impl Runtime {
                     .unwrap();
 
                 if self.scopes.last().unwrap().contains_key(&let_token.name) {
-                    return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
-                        location: Default::default(),
-                    })));
+                    panic!(
+                        "variable '{}' already declared in this scope in {}",
+                        let_token.name, let_token.location
+                    );
                 }
 
                 self.scope_set(
@@ -130,6 +377,44 @@ impl Runtime {
                 let body = loop_token.body.read().unwrap();
 
                 loop {
+                    self.count_step();
+                    let mut break_loop = false;
+
+                    for token in body.iter() {
+                        if self.execute(token).is_none() {
+                            break_loop = true;
+                            break;
+                        }
+                    }
+
+                    if break_loop {
+                        break;
+                    }
+
+                    self.scopes.last_mut().unwrap().clear();
+                    self.modified_vars.borrow_mut().clear();
+                    self.lookup_cache.borrow_mut().clear();
+                }
+
+                self.scopes.pop();
+                self.call_stack.pop();
+
+                self.rebuild_lookup_cache();
+            }
+            Token::Times(times_token) => {
+                let count = match self.extract_value(&times_token.count) {
+                    Some(ValueToken::Number(count)) => count.value.max(0.0) as usize,
+                    _ => panic!("times requires a number in {}", times_token.location),
+                };
+
+                self.call_stack
+                    .push(InsideToken::Times(times_token.clone()));
+                self.scope_create();
+
+                let body = times_token.body.read().unwrap();
+
+                for _ in 0..count {
+                    self.count_step();
                     let mut break_loop = false;
 
                     for token in body.iter() {
@@ -161,6 +446,7 @@ impl Runtime {
                 let body = while_token.body.read().unwrap();
 
                 while self.extract_value(&while_token.condition).unwrap().truthy() {
+                    self.count_step();
                     let mut break_loop = false;
 
                     for token in body.iter() {
@@ -195,6 +481,7 @@ impl Runtime {
                 match value {
                     Some(ValueToken::String(string_value)) => {
                         for c in string_value.value.chars() {
+                            self.count_step();
                             let mut break_loop = false;
 
                             self.scope_set(
@@ -225,6 +512,7 @@ impl Runtime {
                     }
                     Some(ValueToken::Array(array_value)) => {
                         for e in array_value.value.read().unwrap().iter() {
+                            self.count_step();
                             let mut break_loop = false;
 
                             self.scope_set(&foreach_token.item, Arc::new(RwLock::new(e.clone())));
@@ -247,6 +535,7 @@ impl Runtime {
                     }
                     Some(ValueToken::Buffer(buffer_value)) => {
                         for b in buffer_value.value.read().unwrap().iter() {
+                            self.count_step();
                             let mut break_loop = false;
 
                             self.scope_set(
@@ -288,6 +577,7 @@ impl Runtime {
                         };
 
                         for i in start.value as isize..end.value as isize {
+                            self.count_step();
                             let mut break_loop = false;
 
                             self.scope_set(
@@ -328,15 +618,26 @@ impl Runtime {
                 self.call_stack.push(InsideToken::If(if_token.clone()));
 
                 let condition = self.extract_value(&if_token.condition).unwrap();
+                let truthy = match if_token.binding {
+                    // `if let` runs its body for any value but null
+                    Some(_) => !matches!(condition, ValueToken::Null(_)),
+                    None => condition.truthy(),
+                };
 
-                if (if_token.reversed && !condition.truthy())
-                    || (!if_token.reversed && condition.truthy())
-                {
+                if (if_token.reversed && !truthy) || (!if_token.reversed && truthy) {
                     self.scope_create();
 
+                    if let Some(binding) = &if_token.binding {
+                        self.scope_set(
+                            binding,
+                            Arc::new(RwLock::new(ExpressionToken::Value(condition))),
+                        );
+                    }
+
                     let body = if_token.body.read().unwrap();
 
-                    for token in body.iter() {
+                    // skip the binding's placeholder declaration
+                    for token in body.iter().skip(if_token.binding.is_some() as usize) {
                         let value = self.execute(token);
 
                         if value.is_none() {
@@ -362,20 +663,24 @@ impl Runtime {
             }
             Token::Break(_) => {
                 for token in self.call_stack.iter().rev() {
-                    if let InsideToken::Loop(_) = token {
+                    if let InsideToken::Loop(_) | InsideToken::Times(_) = token {
                         return None;
                     }
                 }
             }
             Token::Return(token) => {
                 let value = self.extract_value(&token.value).unwrap();
+                let value = self.resolve_array(value);
 
                 return Some(ExpressionToken::Return(ReturnToken {
                     value: Arc::new(ExpressionToken::Value(value)),
                 }));
             }
             Token::FnCall(call_token) => {
-                if runtime::FUNCTIONS.contains(&call_token.name.as_str()) {
+                if runtime::FUNCTIONS.contains(&call_token.name.as_str())
+                    || call_token.name.contains('#')
+                {
+                    let start = self.profile.is_some().then(Instant::now);
                     let result = runtime::run(
                         call_token.name.as_str(),
                         &call_token.args,
@@ -383,6 +688,12 @@ impl Runtime {
                         &call_token.location,
                     );
 
+                    if let (Some(profile), Some(start)) = (&mut self.profile, start) {
+                        let entry = profile.entry(call_token.name.clone()).or_default();
+                        entry.0 += 1;
+                        entry.1 += start.elapsed();
+                    }
+
                     return result;
                 }
 
@@ -398,47 +709,69 @@ impl Runtime {
                     if let ValueToken::Function(fn_token) =
                         self.extract_value(&fn_var.read().unwrap()).unwrap()
                     {
-                        self.call_stack
-                            .push(InsideToken::Function(fn_token.clone()));
-                        self.scope_create();
+                        let args = call_token
+                            .args
+                            .iter()
+                            .map(|arg| self.extract_value(arg).unwrap())
+                            .collect::<Vec<_>>();
+
+                        Self::check_arity(
+                            &fn_token,
+                            fn_token.args.len(),
+                            args.len(),
+                            &call_token.location,
+                        );
 
-                        for (index, arg) in fn_token.args.iter().enumerate() {
-                            if let Some(arg_expr) = call_token.args.get(index) {
-                                let extracted = self.extract_value(arg_expr).unwrap();
+                        return self.call_function(&fn_token, args);
+                    }
+                }
+            }
+            Token::StaticClassFnCall(call_token) => {
+                let class = self.lookup_variable(&call_token.class);
 
-                                self.scope_set(
-                                    arg,
-                                    Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
-                                );
-                            }
-                        }
+                if let Some(class) = &class
+                    && let ValueToken::Namespace(namespace) =
+                        self.extract_value(&class.read().unwrap()).unwrap()
+                {
+                    let args = call_token
+                        .args
+                        .iter()
+                        .map(|arg| self.extract_value(arg).unwrap())
+                        .collect::<Vec<_>>();
+                    let scope = self.namespace_scope(&namespace);
+
+                    let Some(ValueToken::Function(fn_token)) = scope
+                        .get(&call_token.name)
+                        .map(|value| self.extract_value(&value.read().unwrap()).unwrap())
+                    else {
+                        panic!(
+                            "namespace '{}' has no function '{}'",
+                            namespace.name, call_token.name
+                        );
+                    };
 
-                        let body = fn_token.body.read().unwrap();
+                    Self::check_arity(
+                        &fn_token,
+                        fn_token.args.len(),
+                        args.len(),
+                        &call_token.location,
+                    );
 
-                        for token in body.iter() {
-                            let value = self.execute(token);
+                    // the namespace's members are visible to the function through an
+                    // outer scope around the call
+                    self.scope_create();
+                    self.scopes.last_mut().unwrap().extend(scope);
+                    self.rebuild_lookup_cache();
 
-                            if value.is_none() {
-                                break;
-                            } else if let Some(ExpressionToken::Return(return_token)) = value {
-                                self.scopes.pop();
-                                self.call_stack.pop();
+                    let result = self.call_function(&fn_token, args);
 
-                                self.rebuild_lookup_cache();
-                                return Some(ExpressionToken::Return(return_token));
-                            }
-                        }
+                    self.scopes.pop();
+                    self.rebuild_lookup_cache();
 
-                        self.scopes.pop();
-                        self.call_stack.pop();
-                        self.rebuild_lookup_cache();
+                    if let Some(ExpressionToken::Return(_)) = result {
+                        return result;
                     }
-                }
-            }
-            Token::StaticClassFnCall(call_token) => {
-                let class = self.lookup_variable(&call_token.class);
-
-                if let Some(class) = class
+                } else if let Some(class) = class
                     && let ValueToken::Class(class_token) =
                         self.extract_value(&class.read().unwrap()).unwrap()
                 {
@@ -453,24 +786,34 @@ impl Runtime {
                         && let ValueToken::Function(fn_token) =
                             self.extract_value(&fn_var.read().unwrap()).unwrap()
                     {
+                        Self::check_arity(
+                            &fn_token,
+                            fn_token.args.len(),
+                            call_token.args.len(),
+                            &call_token.location,
+                        );
+
                         self.call_stack
                             .push(InsideToken::Function(fn_token.clone()));
                         self.scope_create();
 
                         for (index, arg) in fn_token.args.iter().enumerate() {
-                            if let Some(arg_expr) = call_token.args.get(index) {
-                                let extracted = self.extract_value(arg_expr).unwrap();
+                            let extracted = match call_token.args.get(index) {
+                                Some(arg_expr) => self.extract_value(arg_expr).unwrap(),
+                                None => ValueToken::Null(NullToken {
+                                    location: Default::default(),
+                                }),
+                            };
 
-                                self.scope_set(
-                                    arg,
-                                    Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
-                                );
-                            }
+                            self.scope_set(
+                                arg,
+                                Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
+                            );
                         }
 
                         let body = fn_token.body.read().unwrap();
 
-                        for token in body.iter() {
+                        for token in body.iter().skip(fn_token.args.len()) {
                             let value = self.execute(token);
 
                             if value.is_none() {
@@ -509,6 +852,14 @@ impl Runtime {
                         && let ValueToken::Function(fn_token) =
                             self.extract_value(&fn_var.read().unwrap()).unwrap()
                     {
+                        // the first parameter is the implicit `self`
+                        Self::check_arity(
+                            &fn_token,
+                            fn_token.args.len().saturating_sub(1),
+                            call_token.args.len(),
+                            &call_token.location,
+                        );
+
                         self.call_stack
                             .push(InsideToken::Function(fn_token.clone()));
                         self.scope_create();
@@ -518,14 +869,17 @@ impl Runtime {
                                 continue;
                             }
 
-                            if let Some(arg_expr) = call_token.args.get(index - 1) {
-                                let extracted = self.extract_value(arg_expr).unwrap();
+                            let extracted = match call_token.args.get(index - 1) {
+                                Some(arg_expr) => self.extract_value(arg_expr).unwrap(),
+                                None => ValueToken::Null(NullToken {
+                                    location: Default::default(),
+                                }),
+                            };
 
-                                self.scope_set(
-                                    arg,
-                                    Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
-                                );
-                            }
+                            self.scope_set(
+                                arg,
+                                Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
+                            );
                         }
 
                         self.scope_set(
@@ -537,7 +891,7 @@ impl Runtime {
 
                         let body = fn_token.body.read().unwrap();
 
-                        for token in body.iter() {
+                        for token in body.iter().skip(fn_token.args.len()) {
                             let value = self.execute(token);
 
                             if value.is_none() {
@@ -610,6 +964,160 @@ impl Runtime {
         })))
     }
 
+    /// Evaluates array elements that still refer to variables, so a returned
+    /// literal like `[x, y]` stays valid after the function scope is popped.
+    fn resolve_array(&mut self, value: ValueToken) -> ValueToken {
+        let ValueToken::Array(array) = &value else {
+            return value;
+        };
+
+        let items = array.value.read().unwrap().clone();
+        let mut changed = false;
+        let mut resolved = Vec::with_capacity(items.len());
+
+        for item in items.iter() {
+            let item_value = match item {
+                ExpressionToken::Value(item_value) => item_value.clone(),
+                _ => {
+                    changed = true;
+                    self.extract_value(item)
+                        .unwrap_or(ValueToken::Null(NullToken {
+                            location: Default::default(),
+                        }))
+                }
+            };
+
+            let resolved_value = self.resolve_array(item_value.clone());
+            if let (ValueToken::Array(before), ValueToken::Array(after)) =
+                (&item_value, &resolved_value)
+                && !Arc::ptr_eq(&before.value, &after.value)
+            {
+                changed = true;
+            }
+
+            resolved.push(ExpressionToken::Value(resolved_value));
+        }
+
+        // arrays that were already fully evaluated keep their identity
+        if !changed {
+            return value;
+        }
+
+        ValueToken::Array(ArrayToken {
+            location: array.location.clone(),
+            value: Arc::new(RwLock::new(resolved)),
+        })
+    }
+
+    /// Rejects a call that passes a different number of arguments than the function declares.
+    fn check_arity(
+        fn_token: &FunctionToken,
+        expected: usize,
+        got: usize,
+        location: &TokenLocation,
+    ) {
+        if expected != got {
+            panic!(
+                "function '{}' expects {expected} {}, got {got} in {location}",
+                fn_token.name,
+                if expected == 1 {
+                    "argument"
+                } else {
+                    "arguments"
+                }
+            );
+        }
+    }
+
+    /// Calls a function value with already evaluated arguments, binding missing
+    /// parameters to null. Yields a `Return` token if the body returned one.
+    pub fn call_function(
+        &mut self,
+        fn_token: &FunctionToken,
+        args: Vec<ValueToken>,
+    ) -> Option<ExpressionToken> {
+        let Some(cache) = &fn_token.cache else {
+            return self.call_function_body(fn_token, args);
+        };
+
+        let key = args
+            .iter()
+            .map(|arg| arg.inspect())
+            .collect::<Vec<_>>()
+            .join("\0");
+
+        if let Some(value) = cache.lock().unwrap().get(&key) {
+            return Some(ExpressionToken::Return(ReturnToken {
+                value: Arc::new(ExpressionToken::Value(value.clone())),
+            }));
+        }
+
+        let result = self.call_function_body(fn_token, args);
+        if let Some(ExpressionToken::Return(return_token)) = &result
+            && let ExpressionToken::Value(value) = &*return_token.value
+        {
+            cache.lock().unwrap().insert(key, value.clone());
+        }
+
+        result
+    }
+
+    fn call_function_body(
+        &mut self,
+        fn_token: &FunctionToken,
+        args: Vec<ValueToken>,
+    ) -> Option<ExpressionToken> {
+        self.call_stack
+            .push(InsideToken::Function(fn_token.clone()));
+        self.scope_create();
+
+        // recursive calls of a memoized function go through its cache too
+        if fn_token.cache.is_some() {
+            self.scope_set(
+                &fn_token.name,
+                Arc::new(RwLock::new(ExpressionToken::Value(ValueToken::Function(
+                    fn_token.clone(),
+                )))),
+            );
+        }
+
+        let mut args = args.into_iter();
+        for arg in fn_token.args.iter() {
+            let extracted = args.next().unwrap_or(ValueToken::Null(NullToken {
+                location: Default::default(),
+            }));
+
+            self.scope_set(
+                arg,
+                Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
+            );
+        }
+
+        let body = fn_token.body.read().unwrap();
+
+        for token in body.iter().skip(fn_token.args.len()) {
+            let value = self.execute(token);
+
+            if value.is_none() {
+                break;
+            } else if let Some(ExpressionToken::Return(return_token)) = value {
+                self.scopes.pop();
+                self.call_stack.pop();
+
+                self.rebuild_lookup_cache();
+                return Some(ExpressionToken::Return(return_token));
+            }
+        }
+
+        self.scopes.pop();
+        self.call_stack.pop();
+        self.rebuild_lookup_cache();
+
+        Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+            location: Default::default(),
+        })))
+    }
+
     pub fn extract_value(&mut self, token: &ExpressionToken) -> Option<ValueToken> {
         match token {
             ExpressionToken::Comparison(comparison_token) => {
@@ -636,60 +1144,69 @@ impl Runtime {
                         }))
                     }
                     ComparisonOperator::GreaterThan => {
-                        if let (ValueToken::Number(left), ValueToken::Number(right)) = (left, right)
-                        {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: left.value > right.value,
-                            }))
-                        } else {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: false,
-                            }))
-                        }
+                        let value = match (left, right) {
+                            (ValueToken::Number(left), ValueToken::Number(right)) => {
+                                left.value > right.value
+                            }
+                            // booleans order as false < true
+                            (ValueToken::Boolean(left), ValueToken::Boolean(right)) => {
+                                left.value.cmp(&right.value).is_gt()
+                            }
+                            _ => false,
+                        };
+
+                        Some(ValueToken::Boolean(BooleanToken {
+                            location: Default::default(),
+                            value,
+                        }))
                     }
                     ComparisonOperator::GreaterThanEquals => {
-                        if let (ValueToken::Number(left), ValueToken::Number(right)) = (left, right)
-                        {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: left.value >= right.value,
-                            }))
-                        } else {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: false,
-                            }))
-                        }
+                        let value = match (left, right) {
+                            (ValueToken::Number(left), ValueToken::Number(right)) => {
+                                left.value >= right.value
+                            }
+                            (ValueToken::Boolean(left), ValueToken::Boolean(right)) => {
+                                left.value.cmp(&right.value).is_ge()
+                            }
+                            _ => false,
+                        };
+
+                        Some(ValueToken::Boolean(BooleanToken {
+                            location: Default::default(),
+                            value,
+                        }))
                     }
                     ComparisonOperator::LessThan => {
-                        if let (ValueToken::Number(left), ValueToken::Number(right)) = (left, right)
-                        {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: left.value < right.value,
-                            }))
-                        } else {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: false,
-                            }))
-                        }
+                        let value = match (left, right) {
+                            (ValueToken::Number(left), ValueToken::Number(right)) => {
+                                left.value < right.value
+                            }
+                            (ValueToken::Boolean(left), ValueToken::Boolean(right)) => {
+                                left.value.cmp(&right.value).is_lt()
+                            }
+                            _ => false,
+                        };
+
+                        Some(ValueToken::Boolean(BooleanToken {
+                            location: Default::default(),
+                            value,
+                        }))
                     }
                     ComparisonOperator::LessThanEquals => {
-                        if let (ValueToken::Number(left), ValueToken::Number(right)) = (left, right)
-                        {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: left.value <= right.value,
-                            }))
-                        } else {
-                            Some(ValueToken::Boolean(BooleanToken {
-                                location: Default::default(),
-                                value: false,
-                            }))
-                        }
+                        let value = match (left, right) {
+                            (ValueToken::Number(left), ValueToken::Number(right)) => {
+                                left.value <= right.value
+                            }
+                            (ValueToken::Boolean(left), ValueToken::Boolean(right)) => {
+                                left.value.cmp(&right.value).is_le()
+                            }
+                            _ => false,
+                        };
+
+                        Some(ValueToken::Boolean(BooleanToken {
+                            location: Default::default(),
+                            value,
+                        }))
                     }
                 }
             }
@@ -707,6 +1224,8 @@ impl Runtime {
             }
             ExpressionToken::Math(expression) => {
                 let mut context = meval::Context::empty();
+                // `%` is tokenized into `mod(a, b)`
+                context.func2("mod", f64::rem_euclid);
 
                 for (name, value) in self.scope_aggregate(false) {
                     if let Ok(guard) = value.read()
@@ -750,21 +1269,38 @@ impl Runtime {
                         if let ExpressionToken::Value(ValueToken::Class(class_token)) =
                             &*var_value.unwrap()
                         {
-                            self.scope_create();
-                            for (i, arg) in value.args.iter().enumerate() {
-                                let value = self.extract_value(arg).unwrap();
+                            // evaluate the arguments before entering the instance scope so a
+                            // failing argument can't leave that scope pushed
+                            let mut args = Vec::new();
+                            for i in 0..class_token.args.len() {
+                                args.push(match value.args.get(i) {
+                                    Some(arg_expr) => self.extract_value(arg_expr)?,
+                                    None => ValueToken::Null(NullToken {
+                                        location: Default::default(),
+                                    }),
+                                });
+                            }
 
+                            self.scope_create();
+                            for (arg, extracted) in class_token.args.iter().zip(args) {
                                 self.scope_set(
-                                    &class_token.args[i],
-                                    Arc::new(RwLock::new(ExpressionToken::Value(value))),
+                                    arg,
+                                    Arc::new(RwLock::new(ExpressionToken::Value(extracted))),
                                 );
                             }
 
-                            for token in class_token.body.read().unwrap().iter() {
+                            for token in class_token
+                                .body
+                                .read()
+                                .unwrap()
+                                .iter()
+                                .skip(class_token.args.len())
+                            {
                                 self.execute(token);
                             }
 
                             let scope = self.scopes.pop().unwrap();
+                            self.rebuild_lookup_cache();
 
                             return Some(ValueToken::ClassInstance(ClassInstanceToken {
                                 class: Arc::new(RwLock::new(class_token.clone())),
@@ -799,7 +1335,102 @@ impl Runtime {
 
                 self.extract_value(&value)
             }
+            ExpressionToken::NamespaceMember(member_token) => {
+                let namespace = self.lookup_variable(&member_token.namespace)?;
+                let namespace = self.extract_value(&namespace.read().unwrap())?;
+
+                let ValueToken::Namespace(namespace) = namespace else {
+                    panic!("{} is not a namespace", member_token.namespace);
+                };
+
+                let scope = self.namespace_scope(&namespace);
+                match scope.get(&member_token.member) {
+                    Some(value) => self.extract_value(&value.read().unwrap()),
+                    None => panic!(
+                        "namespace '{}' has no member '{}'",
+                        namespace.name, member_token.member
+                    ),
+                }
+            }
+            ExpressionToken::Coalesce(coalesce_token) => {
+                match self.extract_value(&coalesce_token.left) {
+                    Some(ValueToken::Null(_)) | None => self.extract_value(&coalesce_token.right),
+                    value => value,
+                }
+            }
+            ExpressionToken::PropertyAccess(access_token) => {
+                let mut value = self.extract_value(&access_token.base)?;
+
+                for property in access_token.path.iter() {
+                    let ValueToken::ClassInstance(class_instance) = value else {
+                        panic!("cannot read property '{property}' of a non-instance value");
+                    };
+
+                    let field = class_instance
+                        .scope
+                        .read()
+                        .unwrap()
+                        .get(property)
+                        .map(Arc::clone);
+                    let Some(field) = field else {
+                        panic!(
+                            "instance of {} has no field '{property}'",
+                            class_instance.class.read().unwrap().name
+                        );
+                    };
+
+                    value = self.extract_value(&field.read().unwrap())?;
+                }
+
+                Some(value)
+            }
+            ExpressionToken::Interpolation(interpolation_token) => {
+                let mut value = String::new();
+
+                for part in interpolation_token.parts.iter() {
+                    match part {
+                        InterpolationPart::Literal(literal) => value.push_str(literal),
+                        InterpolationPart::Variable(name) => {
+                            let Some(variable) = self.lookup_variable(name) else {
+                                panic!(
+                                    "variable '{name}' not found in {}",
+                                    interpolation_token.location
+                                );
+                            };
+
+                            let variable = self.extract_value(&variable.read().unwrap())?;
+                            value.push_str(&variable.value(0));
+                        }
+                    }
+                }
+
+                Some(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value,
+                }))
+            }
             ExpressionToken::Return(value) => self.extract_value(&value.value),
         }
     }
+
+    fn namespace_scope(&mut self, namespace: &NamespaceToken) -> Scope {
+        if let Some(scope) = &*namespace.scope.read().unwrap() {
+            return scope.clone();
+        }
+
+        // the module top level runs in isolation, without access to the importing scope
+        let scopes = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+        self.rebuild_lookup_cache();
+
+        for token in namespace.body.read().unwrap().iter() {
+            self.execute(token);
+        }
+
+        let scope = std::mem::replace(&mut self.scopes, scopes).pop().unwrap();
+        self.rebuild_lookup_cache();
+
+        *namespace.scope.write().unwrap() = Some(scope.clone());
+
+        scope
+    }
 }
@@ -242,19 +242,7 @@ pub struct BufferToken {
 impl PartialEq<ValueToken> for BufferToken {
     fn eq(&self, _other: &ValueToken) -> bool {
         if let ValueToken::Buffer(other) = _other {
-            for (left, right) in self
-                .value
-                .read()
-                .unwrap()
-                .iter()
-                .zip(other.value.read().unwrap().iter())
-            {
-                if left != right {
-                    return false;
-                }
-            }
-
-            true
+            self == other
         } else {
             false
         }
@@ -263,13 +251,18 @@ impl PartialEq<ValueToken> for BufferToken {
 
 impl PartialEq<BufferToken> for BufferToken {
     fn eq(&self, other: &BufferToken) -> bool {
-        for (left, right) in self
-            .value
-            .read()
-            .unwrap()
-            .iter()
-            .zip(other.value.read().unwrap().iter())
-        {
+        if Arc::ptr_eq(&self.value, &other.value) {
+            return true;
+        }
+
+        let left = self.value.read().unwrap();
+        let right = other.value.read().unwrap();
+
+        if left.len() != right.len() {
+            return false;
+        }
+
+        for (left, right) in left.iter().zip(right.iter()) {
             if left != right {
                 return false;
             }
@@ -382,19 +375,26 @@ pub struct FunctionToken {
     pub name: String,
     pub args: Vec<String>,
     pub body: Arc<RwLock<Vec<Token>>>,
+    /// Results keyed by inspected arguments, set on functions made by `#memoize`
+    pub cache: Option<Arc<Mutex<HashMap<String, ValueToken>>>>,
 
     pub location: TokenLocation,
 }
 
 impl PartialEq<ValueToken> for FunctionToken {
-    fn eq(&self, _other: &ValueToken) -> bool {
-        false
+    fn eq(&self, other: &ValueToken) -> bool {
+        if let ValueToken::Function(other) = other {
+            self == other
+        } else {
+            false
+        }
     }
 }
 
 impl PartialEq<FunctionToken> for FunctionToken {
-    fn eq(&self, _other: &FunctionToken) -> bool {
-        false
+    fn eq(&self, other: &FunctionToken) -> bool {
+        // copies of a function share its body, which serves as its identity
+        Arc::ptr_eq(&self.body, &other.body)
     }
 }
 
@@ -491,6 +491,47 @@ impl BaseToken for ClassInstanceToken {
     }
 }
 
+pub type Scope = HashMap<String, Arc<RwLock<ExpressionToken>>>;
+
+#[derive(Debug, Clone)]
+pub struct NamespaceToken {
+    pub name: String,
+    pub body: Arc<RwLock<Vec<Token>>>,
+    pub scope: Arc<RwLock<Option<Scope>>>,
+
+    pub location: TokenLocation,
+}
+
+impl PartialEq<ValueToken> for NamespaceToken {
+    fn eq(&self, _other: &ValueToken) -> bool {
+        false
+    }
+}
+
+impl PartialEq<NamespaceToken> for NamespaceToken {
+    fn eq(&self, _other: &NamespaceToken) -> bool {
+        false
+    }
+}
+
+impl BaseToken for NamespaceToken {
+    fn inspect(&self) -> String {
+        format!(
+            "Namespace({}) {{ <{} tokens> }}",
+            self.name,
+            self.body.read().unwrap().len()
+        )
+    }
+
+    fn value(&self, _: usize) -> String {
+        self.inspect()
+    }
+
+    fn truthy(&self) -> bool {
+        true
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ValueToken {
     String(StringToken),
@@ -504,6 +545,7 @@ pub enum ValueToken {
     Function(FunctionToken),
     Class(ClassToken),
     ClassInstance(ClassInstanceToken),
+    Namespace(NamespaceToken),
 }
 
 impl PartialEq<ValueToken> for ValueToken {
@@ -520,6 +562,7 @@ impl PartialEq<ValueToken> for ValueToken {
             (ValueToken::Function(left), ValueToken::Function(right)) => left == right,
             (ValueToken::Class(left), ValueToken::Class(right)) => left == right,
             (ValueToken::ClassInstance(left), ValueToken::ClassInstance(right)) => left == right,
+            (ValueToken::Namespace(left), ValueToken::Namespace(right)) => left == right,
             _ => false,
         }
     }
@@ -539,6 +582,7 @@ impl BaseToken for ValueToken {
             ValueToken::Function(function_token) => function_token.inspect(),
             ValueToken::Class(class_token) => class_token.inspect(),
             ValueToken::ClassInstance(class_instance_token) => class_instance_token.inspect(),
+            ValueToken::Namespace(namespace_token) => namespace_token.inspect(),
         }
     }
 
@@ -555,6 +599,7 @@ impl BaseToken for ValueToken {
             ValueToken::Function(function_token) => function_token.value(spaces),
             ValueToken::Class(class_token) => class_token.value(spaces),
             ValueToken::ClassInstance(class_instance_token) => class_instance_token.value(spaces),
+            ValueToken::Namespace(namespace_token) => namespace_token.value(spaces),
         }
     }
 
@@ -571,6 +616,7 @@ impl BaseToken for ValueToken {
             ValueToken::Function(function_token) => function_token.truthy(),
             ValueToken::Class(class_token) => class_token.truthy(),
             ValueToken::ClassInstance(class_instance_token) => class_instance_token.truthy(),
+            ValueToken::Namespace(namespace_token) => namespace_token.truthy(),
         }
     }
 }
@@ -589,6 +635,7 @@ impl ValueToken {
             ValueToken::Function(token) => token.location.clone(),
             ValueToken::Class(token) => token.location.clone(),
             ValueToken::ClassInstance(_) => TokenLocation::default(),
+            ValueToken::Namespace(token) => token.location.clone(),
         }
     }
 }
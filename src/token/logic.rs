@@ -17,6 +17,8 @@ pub struct LetToken {
     pub is_function: bool,
     pub is_class: bool,
     pub value: Arc<RwLock<ExpressionToken>>,
+
+    pub location: TokenLocation,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +29,10 @@ pub enum ExpressionToken {
     ClassInstantiation(ClassInstantiationToken),
     StaticClassFnCall(StaticClassFnCallToken),
     ClassFnCall(ClassFnCallToken),
+    NamespaceMember(NamespaceMemberToken),
+    Coalesce(CoalesceToken),
+    PropertyAccess(PropertyAccessToken),
+    Interpolation(InterpolationToken),
     Value(ValueToken),
     Math(meval::Expr),
     Let(LetToken),
@@ -64,6 +70,8 @@ pub struct StaticClassFnCallToken {
     pub name: String,
     pub class: String,
     pub args: Vec<Arc<ExpressionToken>>,
+
+    pub location: TokenLocation,
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +79,39 @@ pub struct ClassFnCallToken {
     pub name: String,
     pub instance: String,
     pub args: Vec<Arc<ExpressionToken>>,
+
+    pub location: TokenLocation,
+}
+
+#[derive(Debug, Clone)]
+pub struct NamespaceMemberToken {
+    pub namespace: String,
+    pub member: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoalesceToken {
+    pub left: Arc<ExpressionToken>,
+    pub right: Arc<ExpressionToken>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PropertyAccessToken {
+    pub base: Arc<ExpressionToken>,
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum InterpolationPart {
+    Literal(String),
+    Variable(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct InterpolationToken {
+    pub parts: Vec<InterpolationPart>,
+
+    pub location: TokenLocation,
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +119,14 @@ pub struct LoopToken {
     pub body: Arc<RwLock<Vec<Token>>>,
 }
 
+#[derive(Debug, Clone)]
+pub struct TimesToken {
+    pub count: Arc<ExpressionToken>,
+    pub body: Arc<RwLock<Vec<Token>>>,
+
+    pub location: TokenLocation,
+}
+
 #[derive(Debug, Clone)]
 pub struct WhileToken {
     pub condition: Arc<ExpressionToken>,
@@ -94,6 +143,7 @@ pub struct ForeachToken {
 #[derive(Debug, Clone)]
 pub struct IfToken {
     pub reversed: bool,
+    pub binding: Option<String>,
     pub condition: Arc<ExpressionToken>,
     pub body: Arc<RwLock<Vec<Token>>>,
 }
@@ -5,14 +5,15 @@ pub mod macros;
 pub mod runtime;
 
 use base::{
-    ArrayToken, BooleanToken, ClassToken, FunctionToken, NullToken, NumberToken, RangeToken,
-    StringToken, ValueToken,
+    ArrayToken, BooleanToken, ClassInstanceToken, ClassToken, FunctionToken, NamespaceToken,
+    NullToken, NumberToken, RangeToken, Scope, StringToken, ValueToken,
 };
 use comparison::{COMPARISON_OPERATORS, ComparisonToken};
 use logic::{
-    BreakToken, ClassFnCallToken, ClassInstantiationToken, ExpressionToken, FnCallToken,
-    ForeachToken, IfToken, LetAssignNumToken, LetAssignToken, LetToken, LoopToken, ReturnToken,
-    StaticClassFnCallToken, WhileToken,
+    BreakToken, ClassFnCallToken, ClassInstantiationToken, CoalesceToken, ExpressionToken,
+    FnCallToken, ForeachToken, IfToken, InterpolationPart, InterpolationToken, LetAssignNumToken,
+    LetAssignToken, LetToken, LoopToken, NamespaceMemberToken, PropertyAccessToken, ReturnToken,
+    StaticClassFnCallToken, TimesToken, WhileToken,
 };
 use std::{
     collections::HashMap,
@@ -51,6 +52,7 @@ pub enum Token {
     StaticClassFnCall(StaticClassFnCallToken),
     ClassFnCall(ClassFnCallToken),
     Loop(LoopToken),
+    Times(TimesToken),
     While(WhileToken),
     Foreach(ForeachToken),
     Break(BreakToken),
@@ -61,6 +63,7 @@ pub enum Token {
 pub enum InsideToken {
     Function(FunctionToken),
     Loop(LoopToken),
+    Times(TimesToken),
     While(WhileToken),
     Foreach(ForeachToken),
     If(IfToken),
@@ -76,6 +79,10 @@ pub struct Tokenizer {
 
     pub tokens: Vec<Token>,
     inside: Vec<Arc<Mutex<InsideToken>>>,
+    imports: HashMap<String, NamespaceToken>,
+    including: Vec<String>,
+    /// User-defined infix operators (`operator <> = my_func`) as (symbol, function name)
+    operators: Vec<(String, String)>,
 }
 
 impl Tokenizer {
@@ -95,19 +102,452 @@ impl Tokenizer {
             ]),
             tokens: Vec::new(),
             inside: Vec::new(),
+            imports: HashMap::new(),
+            including: vec![Self::canonical_path(file)],
+            operators: Vec::new(),
         }
     }
 
     pub fn parse(&mut self) {
         self.tokens.clear();
 
+        let mut statement = String::new();
+        let mut start_line = 0;
+        let mut depth = 0;
+
         for line in self.input.clone().lines() {
             self.location.line += 1;
 
-            if let Some(token) = self.tokenize(line) {
+            let line = line.trim();
+
+            // comments are dropped even between the lines of a bracketed statement
+            if Self::is_comment(line) {
+                continue;
+            }
+
+            if statement.is_empty() {
+                start_line = self.location.line;
+            } else if !statement.ends_with(['(', '[']) && !line.starts_with([')', ']']) {
+                statement.push(' ');
+            }
+
+            statement.push_str(line);
+            depth += Self::bracket_depth(line);
+
+            // keep buffering lines until every opened bracket has been closed
+            if depth > 0 {
+                continue;
+            }
+
+            // enum bodies may span lines
+            if statement.starts_with("enum ") && !statement.ends_with('}') {
+                continue;
+            }
+
+            let end_line = self.location.line;
+            self.location.line = start_line;
+
+            if let Some(token) = self.tokenize(&std::mem::take(&mut statement)) {
                 self.push_token(token);
             }
+
+            self.location.line = end_line;
+            depth = 0;
+        }
+
+        if !statement.is_empty() {
+            self.location.line = start_line;
+
+            if let Some(token) = self.tokenize(&statement) {
+                self.push_token(token);
+            }
+        }
+    }
+
+    fn is_comment(line: &str) -> bool {
+        line.starts_with("//")
+            || (line.starts_with("#")
+                && !runtime::FUNCTIONS
+                    .iter()
+                    .any(|func| line.starts_with(&format!("{func}("))))
+    }
+
+    /// Returns the name of a `namespace#name(...)` call outside the built-in namespaces,
+    /// which an embedding host may register on the runtime.
+    fn host_fn_name(segment: &str) -> Option<&str> {
+        let (name, _) = segment.split_once('(')?;
+        let (namespace, function) = name.split_once('#')?;
+        let is_identifier = |part: &str| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+
+        if !segment.ends_with(')') || !is_identifier(namespace) || !is_identifier(function) {
+            return None;
+        }
+
+        // keep typos in built-in namespaces a parse error
+        if runtime::FUNCTIONS
+            .iter()
+            .any(|func| func.starts_with(&format!("{namespace}#")))
+        {
+            return None;
+        }
+
+        Some(name)
+    }
+
+    fn bracket_depth(line: &str) -> isize {
+        let mut depth = 0;
+        let mut in_string = false;
+
+        for c in line.chars() {
+            if c == '"' {
+                in_string = !in_string;
+            } else if !in_string {
+                if c == '(' || c == '[' {
+                    depth += 1;
+                } else if c == ')' || c == ']' {
+                    depth -= 1;
+                }
+            }
+        }
+
+        depth
+    }
+
+    /// Finds `needle` in `segment` outside of strings and brackets.
+    fn find_top_level(segment: &str, needle: &str) -> Option<usize> {
+        let mut depth = 0;
+        let mut in_string = false;
+
+        for (index, c) in segment.char_indices() {
+            if c == '"' {
+                in_string = !in_string;
+            } else if !in_string {
+                if c == '(' || c == '[' {
+                    depth += 1;
+                } else if c == ')' || c == ']' {
+                    depth -= 1;
+                } else if depth == 0 && segment[index..].starts_with(needle) {
+                    return Some(index);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn unescape(value: &str) -> String {
+        value
+            .replace("\\n", "\n")
+            .replace("\\r", "\r")
+            .replace("\\t", "\t")
+            .replace("\\\\", "\\")
+    }
+
+    /// Splits a string literal's contents on `{identifier}` placeholders, turning `{{`
+    /// and `}}` into literal braces. Returns `None` when the literal contains neither,
+    /// so braces that don't wrap an identifier (like `string#format`'s `{}`) are kept.
+    fn parse_interpolation(content: &str) -> Option<Vec<InterpolationPart>> {
+        let is_identifier = |name: &str| {
+            name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut interpolated = false;
+        let mut rest = content;
+
+        while let Some(index) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..index]);
+            let tail = &rest[index..];
+
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                literal.push_str(&tail[..1]);
+                rest = &tail[2..];
+                interpolated = true;
+            } else if let Some(end) = tail.find('}')
+                && tail.starts_with('{')
+                && is_identifier(&tail[1..end])
+            {
+                if !literal.is_empty() {
+                    parts.push(InterpolationPart::Literal(Self::unescape(&literal)));
+                    literal.clear();
+                }
+
+                parts.push(InterpolationPart::Variable(tail[1..end].to_string()));
+                rest = &tail[end + 1..];
+                interpolated = true;
+            } else {
+                literal.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+
+        if !interpolated {
+            return None;
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(InterpolationPart::Literal(Self::unescape(&literal)));
+        }
+
+        Some(parts)
+    }
+
+    /// Splits `segment` at top-level characters accepted by `split`, which also receives
+    /// the previous non-space character. Each part is paired with the separator before it.
+    fn split_top_level(
+        segment: &str,
+        split: impl Fn(Option<char>, char) -> bool,
+    ) -> Vec<(Option<char>, String)> {
+        let mut parts = vec![(None, String::new())];
+        let mut depth = 0;
+        let mut previous = None;
+
+        for c in segment.chars() {
+            if c == '(' {
+                depth += 1;
+            } else if c == ')' {
+                depth -= 1;
+            }
+
+            if depth == 0 && split(previous, c) {
+                parts.push((Some(c), String::new()));
+            } else {
+                parts.last_mut().unwrap().1.push(c);
+            }
+
+            if !c.is_whitespace() {
+                previous = Some(c);
+            }
+        }
+
+        parts
+    }
+
+    /// Rewrites `a % b` as `mod(a, b)`, which the runtime evaluates with `rem_euclid`
+    /// like `math#mod`, keeping `%` at the precedence of `*` and `/`.
+    fn rewrite_modulo(segment: &str) -> String {
+        // a `+` or `-` directly after an operand is binary, anything else is a sign
+        let terms = Self::split_top_level(segment, |previous, c| {
+            (c == '+' || c == '-')
+                && previous.is_some_and(|p| p.is_alphanumeric() || p == '_' || p == '.' || p == ')')
+        });
+
+        terms
+            .into_iter()
+            .map(|(operator, term)| {
+                let factors = Self::split_top_level(&term, |_, c| matches!(c, '*' | '/' | '%'));
+                let mut result = String::new();
+
+                for (operator, factor) in factors {
+                    let factor = Self::rewrite_groups(factor.trim());
+
+                    result = match operator {
+                        Some('%') => format!("mod({result}, {factor})"),
+                        Some(operator) => format!("{result} {operator} {factor}"),
+                        None => factor,
+                    };
+                }
+
+                match operator {
+                    Some(operator) => format!(" {operator} {result}"),
+                    None => result,
+                }
+            })
+            .collect()
+    }
+
+    /// Applies `rewrite_modulo` inside each parenthesized group of `factor`,
+    /// argument by argument for function calls.
+    fn rewrite_groups(factor: &str) -> String {
+        let mut result = String::new();
+        let mut group = String::new();
+        let mut depth = 0;
+
+        for c in factor.chars() {
+            if c == ')' {
+                depth -= 1;
+
+                if depth == 0 {
+                    let args = Self::split_top_level(&group, |_, c| c == ',')
+                        .into_iter()
+                        .map(|(_, arg)| Self::rewrite_modulo(arg.trim()))
+                        .collect::<Vec<_>>();
+
+                    result.push_str(&args.join(", "));
+                    group.clear();
+                }
+            }
+
+            if depth > 0 {
+                group.push(c);
+            } else {
+                result.push(c);
+            }
+
+            if c == '(' {
+                depth += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Declares `enum Name { A, B, C = "c" }` as a const instance holding one field per
+    /// variant, so variants read as `Name.A`. Variants without a value are numbered by
+    /// their position.
+    fn tokenize_enum(&self, segment: &str) -> Token {
+        let Some((name, variants)) = segment[5..]
+            .split_once('{')
+            .and_then(|(name, body)| Some((name.trim(), body.trim().strip_suffix('}')?)))
+        else {
+            panic!("invalid enum in {}", self.location);
+        };
+
+        let mut scope = Scope::new();
+        let variants = variants.split(',').map(str::trim).filter(|v| !v.is_empty());
+
+        for (index, variant) in variants.enumerate() {
+            let (variant, value) = match variant.split_once('=') {
+                Some((variant, value)) => {
+                    let Some(value) = self.parse_expression(value.trim()) else {
+                        panic!("unexpected value in {} (did you typo?)", self.location);
+                    };
+
+                    (variant.trim(), value)
+                }
+                None => (
+                    variant,
+                    ExpressionToken::Value(ValueToken::Number(NumberToken {
+                        value: index as f64,
+                        location: self.location(),
+                    })),
+                ),
+            };
+
+            if scope
+                .insert(variant.to_string(), Arc::new(RwLock::new(value)))
+                .is_some()
+            {
+                panic!("duplicate variant '{variant}' in {}", self.location);
+            }
         }
+
+        let class = ClassToken {
+            name: name.to_string(),
+            args: Vec::new(),
+            body: Arc::new(RwLock::new(Vec::new())),
+            location: self.location(),
+        };
+
+        Token::Let(LetToken {
+            name: name.to_string(),
+            is_const: true,
+            is_function: false,
+            is_class: false,
+            value: Arc::new(RwLock::new(ExpressionToken::Value(
+                ValueToken::ClassInstance(ClassInstanceToken {
+                    class: Arc::new(RwLock::new(class)),
+                    scope: Arc::new(RwLock::new(scope)),
+                    location: self.location(),
+                }),
+            ))),
+
+            location: self.location(),
+        })
+    }
+
+    /// Desugars `let [a, b] = value` into a hidden binding holding `value`, named after
+    /// the pattern itself, followed by one `let` per name reading its element.
+    fn tokenize_destructure(&mut self, segment: &str) -> Option<Token> {
+        let rest = segment[3..].trim_start();
+        let (is_const, rest) = match rest.strip_prefix("const ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, rest),
+        };
+
+        let pattern = rest.strip_prefix("[")?;
+        let (names, value) = pattern
+            .split_once("]")
+            .and_then(|(names, value)| Some((names, value.trim().strip_prefix("=")?)))
+            .unwrap_or_else(|| panic!("invalid destructuring in {}", self.location));
+
+        let names = names
+            .split(",")
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<&str>>();
+        if names.is_empty()
+            || !names
+                .iter()
+                .all(|name| name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        {
+            panic!("invalid destructuring in {}", self.location);
+        }
+
+        let value = self
+            .parse_expression(value.trim())
+            .unwrap_or_else(|| panic!("unexpected value in {} (did you typo?)", self.location));
+
+        let source = format!("[{}]", names.join(", "));
+        self.push_token(Token::Let(LetToken {
+            name: source.clone(),
+            is_const: true,
+            is_function: false,
+            is_class: false,
+            value: Arc::new(RwLock::new(value)),
+
+            location: self.location(),
+        }));
+
+        let mut tokens = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                Token::Let(LetToken {
+                    name: name.to_string(),
+                    is_const,
+                    is_function: false,
+                    is_class: false,
+                    value: Arc::new(RwLock::new(ExpressionToken::FnCall(FnCallToken {
+                        name: "array#get".to_string(),
+                        args: vec![
+                            Arc::new(ExpressionToken::Let(LetToken {
+                                name: source.clone(),
+                                is_const: true,
+                                is_function: false,
+                                is_class: false,
+                                value: Arc::new(RwLock::new(ExpressionToken::Value(
+                                    ValueToken::Null(NullToken {
+                                        location: self.location(),
+                                    }),
+                                ))),
+
+                                location: self.location(),
+                            })),
+                            Arc::new(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                                location: self.location(),
+                                value: index as f64,
+                            }))),
+                        ],
+                        location: self.location(),
+                    }))),
+
+                    location: self.location(),
+                })
+            })
+            .collect::<Vec<Token>>();
+
+        let last = tokens.pop();
+        for token in tokens {
+            self.push_token(token);
+        }
+
+        last
     }
 
     fn push_token(&mut self, token: Token) {
@@ -119,6 +559,9 @@ impl Tokenizer {
                 InsideToken::Loop(loop_token) => {
                     loop_token.body.write().unwrap().push(token);
                 }
+                InsideToken::Times(times_token) => {
+                    times_token.body.write().unwrap().push(token);
+                }
                 InsideToken::While(while_token) => {
                     while_token.body.write().unwrap().push(token);
                 }
@@ -159,6 +602,12 @@ impl Tokenizer {
                         Self::add_nested_tokens(Self::check_if_is_inside(token), &mut tokens);
                     }
                 }
+                InsideToken::Times(times_token) => {
+                    for token in times_token.body.read().unwrap().iter() {
+                        tokens.push(token.clone());
+                        Self::add_nested_tokens(Self::check_if_is_inside(token), &mut tokens);
+                    }
+                }
                 InsideToken::While(while_token) => {
                     for token in while_token.body.read().unwrap().iter() {
                         tokens.push(token.clone());
@@ -194,6 +643,9 @@ impl Tokenizer {
             Token::Loop(loop_token) => {
                 return Some(InsideToken::Loop(loop_token.clone()));
             }
+            Token::Times(times_token) => {
+                return Some(InsideToken::Times(times_token.clone()));
+            }
             Token::If(if_token) => {
                 return Some(InsideToken::If(if_token.clone()));
             }
@@ -235,6 +687,12 @@ impl Tokenizer {
                     Self::add_nested_tokens(Self::check_if_is_inside(token), tokens);
                 }
             }
+            InsideToken::Times(times_token) => {
+                for token in times_token.body.read().unwrap().iter() {
+                    tokens.push(token.clone());
+                    Self::add_nested_tokens(Self::check_if_is_inside(token), tokens);
+                }
+            }
             InsideToken::While(while_token) => {
                 for token in while_token.body.read().unwrap().iter() {
                     tokens.push(token.clone());
@@ -265,7 +723,7 @@ impl Tokenizer {
     pub fn tokenize(&mut self, mut segment: &str) -> Option<Token> {
         segment = segment.trim();
 
-        if segment.is_empty() || segment.starts_with("//") || segment.starts_with("#") {
+        if segment.is_empty() || Self::is_comment(segment) {
             return None;
         }
 
@@ -286,19 +744,20 @@ impl Tokenizer {
             let file = self.parse_expression(parts[1]);
             if let Some(file) = file {
                 if let ExpressionToken::Value(ValueToken::String(string_token)) = file {
-                    let file = std::fs::read_to_string(&string_token.value);
+                    let path = self.resolve_path(&string_token.value);
+                    let file = std::fs::read_to_string(&path);
                     if let Ok(file) = file {
-                        let mut tokenizer = Tokenizer::new(&file, &string_token.value);
-                        tokenizer.parse();
+                        let tokenizer = self.tokenize_file(&file, &path);
 
                         for token in tokenizer.tokens {
+                            if let Token::Let(let_token) = &token {
+                                self.check_include_collision(let_token);
+                            }
+
                             self.push_token(token);
                         }
                     } else {
-                        panic!(
-                            "unable to read file \"{}\" in {}",
-                            string_token.value, self.location
-                        );
+                        panic!("unable to read file \"{}\" in {}", path, self.location);
                     }
                 } else {
                     panic!("unexpected value in {} (did you typo?)", self.location);
@@ -318,37 +777,48 @@ impl Tokenizer {
             let file = self.parse_expression(parts[1]);
             if let Some(file) = file {
                 if let ExpressionToken::Value(ValueToken::String(string_token)) = file {
-                    let class = ClassToken {
-                        name: name.to_string(),
-                        args: Vec::new(),
-                        body: Arc::new(RwLock::new(Vec::new())),
+                    let path = self.resolve_path(&string_token.value);
 
-                        location: self.location(),
-                    };
+                    // importing the same file twice shares its tokens and evaluated scope,
+                    // so the top level of a module only ever runs once
+                    let key = Self::canonical_path(&path);
 
-                    let file = std::fs::read_to_string(&string_token.value);
-                    if let Ok(file) = file {
-                        let mut tokenizer = Tokenizer::new(&file, &string_token.value);
-                        tokenizer.parse();
-
-                        for token in tokenizer.tokens {
-                            class.body.write().unwrap().push(token);
+                    let namespace = if let Some(namespace) = self.imports.get(&key) {
+                        NamespaceToken {
+                            name: name.to_string(),
+                            location: self.location(),
+                            ..namespace.clone()
                         }
                     } else {
-                        panic!(
-                            "unable to read file \"{}\" in {}",
-                            string_token.value, self.location
-                        );
-                    }
+                        let file = std::fs::read_to_string(&path);
+                        if let Ok(file) = file {
+                            let tokenizer = self.tokenize_file(&file, &path);
+
+                            let namespace = NamespaceToken {
+                                name: name.to_string(),
+                                body: Arc::new(RwLock::new(tokenizer.tokens)),
+                                scope: Arc::new(RwLock::new(None)),
+
+                                location: self.location(),
+                            };
+
+                            self.imports.insert(key, namespace.clone());
+                            namespace
+                        } else {
+                            panic!("unable to read file \"{}\" in {}", path, self.location);
+                        }
+                    };
 
                     let token = Token::Let(LetToken {
                         name: name.to_string(),
                         is_const: true,
                         is_function: false,
-                        is_class: true,
-                        value: Arc::new(RwLock::new(ExpressionToken::Value(ValueToken::Class(
-                            class,
-                        )))),
+                        is_class: false,
+                        value: Arc::new(RwLock::new(ExpressionToken::Value(
+                            ValueToken::Namespace(namespace),
+                        ))),
+
+                        location: self.location(),
                     });
 
                     return Some(token);
@@ -358,8 +828,64 @@ impl Tokenizer {
             } else {
                 panic!("unexpected file in {} (did you typo?)", self.location);
             }
-        } else if segment.starts_with("let") {
+        } else if segment.starts_with("operator ") && segment.split_whitespace().nth(2) == Some("=")
+        {
+            let parts: Vec<&str> = segment.split_whitespace().collect();
+            if parts.len() != 4
+                || parts[1]
+                    .chars()
+                    .any(|c| c.is_alphanumeric() || "_\"()[]{}".contains(c))
+            {
+                panic!("invalid operator definition in {}", self.location);
+            }
+
+            let (symbol, function) = (parts[1], parts[3]);
+            if !self
+                .current_tokens_context()
+                .iter()
+                .any(|token| matches!(token, Token::Let(let_token) if let_token.name == function))
+            {
+                panic!("function '{function}' not found in {}", self.location);
+            }
+
+            self.operators.retain(|(existing, _)| existing != symbol);
+            self.operators
+                .push((symbol.to_string(), function.to_string()));
+
+            return None;
+        } else if segment.starts_with("enum ") {
+            return Some(self.tokenize_enum(segment));
+        } else if segment.starts_with("let ") {
+            if let Some(token) = self.tokenize_destructure(segment) {
+                return Some(token);
+            }
+
             let parts: Vec<&str> = segment.split_whitespace().collect();
+
+            // a bare `let x` declares the variable as null until it's assigned
+            if parts.len() == 2 && parts[1] != "const" {
+                let is_identifier = !parts[1].starts_with(|c: char| c.is_ascii_digit())
+                    && parts[1].chars().all(|c| c.is_alphanumeric() || c == '_');
+
+                if !is_identifier {
+                    panic!("unexpected token in {}", self.location);
+                }
+
+                return Some(Token::Let(LetToken {
+                    name: parts[1].to_string(),
+                    is_const: false,
+                    is_function: false,
+                    is_class: false,
+                    value: Arc::new(RwLock::new(ExpressionToken::Value(ValueToken::Null(
+                        NullToken {
+                            location: self.location(),
+                        },
+                    )))),
+
+                    location: self.location(),
+                }));
+            }
+
             if parts.len() < 3 {
                 return None;
             }
@@ -384,8 +910,10 @@ impl Tokenizer {
                 is_function: false,
                 is_class: false,
                 value: Arc::new(RwLock::new(value.unwrap())),
+
+                location: self.location(),
             }));
-        } else if segment.starts_with("class") {
+        } else if segment.starts_with("class ") {
             let parts: Vec<&str> = segment.split("(").collect();
             if parts.len() != 2 {
                 return None;
@@ -413,6 +941,8 @@ impl Tokenizer {
                             location: self.location(),
                         },
                     )))),
+
+                    location: self.location(),
                 }));
             }
 
@@ -432,6 +962,8 @@ impl Tokenizer {
                         location: self.location(),
                     },
                 )))),
+
+                location: self.location(),
             });
 
             self.push_token(token);
@@ -474,6 +1006,8 @@ impl Tokenizer {
                             location: self.location(),
                         },
                     )))),
+
+                    location: self.location(),
                 }));
             }
 
@@ -483,6 +1017,7 @@ impl Tokenizer {
                 name: name.clone(),
                 args: args.clone(),
                 body: Arc::clone(&body),
+                cache: None,
 
                 location: self.location(),
             });
@@ -493,6 +1028,8 @@ impl Tokenizer {
                 is_function: true,
                 is_class: false,
                 value: Arc::new(RwLock::new(ExpressionToken::Value(value))),
+
+                location: self.location(),
             });
 
             self.push_token(token);
@@ -501,6 +1038,7 @@ impl Tokenizer {
                     name,
                     args,
                     body,
+                    cache: None,
 
                     location: self.location(),
                 }))));
@@ -516,6 +1054,35 @@ impl Tokenizer {
             self.inside
                 .push(Arc::new(Mutex::new(InsideToken::Loop(LoopToken { body }))));
 
+            return None;
+        } else if segment.starts_with("times ") && segment.ends_with("{") {
+            let count = segment[6..segment.len() - 1].trim();
+            let count = count
+                .strip_prefix("(")
+                .and_then(|count| count.strip_suffix(")"))
+                .unwrap_or(count);
+
+            let count = Arc::new(self.parse_expression(count.trim()).unwrap_or_else(|| {
+                panic!("unexpected count in {} (did you typo?)", self.location)
+            }));
+
+            let body = Arc::new(RwLock::new(Vec::new()));
+            let token = Token::Times(TimesToken {
+                count: Arc::clone(&count),
+                body: Arc::clone(&body),
+
+                location: self.location(),
+            });
+
+            self.push_token(token);
+            self.inside
+                .push(Arc::new(Mutex::new(InsideToken::Times(TimesToken {
+                    count,
+                    body,
+
+                    location: self.location(),
+                }))));
+
             return None;
         } else if segment.starts_with("while") {
             let condition = self.parse_expression(segment[7..segment.len() - 3].trim());
@@ -571,7 +1138,9 @@ impl Tokenizer {
                 }))));
 
             return None;
-        } else if segment.starts_with("return") && !self.inside.is_empty() {
+        } else if segment.starts_with("return")
+            && (!self.inside.is_empty() || segment == "return" || segment.starts_with("return "))
+        {
             if segment.len() < 7 {
                 return Some(Token::Return(ReturnToken {
                     value: Arc::new(ExpressionToken::Value(ValueToken::Null(NullToken {
@@ -588,6 +1157,51 @@ impl Tokenizer {
             return Some(Token::Return(ReturnToken {
                 value: Arc::new(value.unwrap()),
             }));
+        } else if let Some(binding) = segment.strip_prefix("if let ") {
+            let Some((name, condition)) = binding
+                .strip_suffix("{")
+                .and_then(|binding| binding.split_once('='))
+            else {
+                panic!("unexpected condition in {} (did you typo?)", self.location);
+            };
+
+            let name = name.trim().to_string();
+            let condition =
+                Arc::new(self.parse_expression(condition.trim()).unwrap_or_else(|| {
+                    panic!("unexpected condition in {} (did you typo?)", self.location)
+                }));
+
+            // declares the binding for the body, the runtime sets its value instead
+            let body = Arc::new(RwLock::new(vec![Token::Let(LetToken {
+                name: name.clone(),
+                is_const: false,
+                is_function: false,
+                is_class: false,
+                value: Arc::new(RwLock::new(ExpressionToken::Value(ValueToken::Null(
+                    NullToken {
+                        location: self.location(),
+                    },
+                )))),
+
+                location: self.location(),
+            })]));
+            let token = Token::If(IfToken {
+                reversed: false,
+                binding: Some(name.clone()),
+                condition: Arc::clone(&condition),
+                body: Arc::clone(&body),
+            });
+
+            self.push_token(token);
+            self.inside
+                .push(Arc::new(Mutex::new(InsideToken::If(IfToken {
+                    reversed: false,
+                    binding: Some(name),
+                    condition,
+                    body,
+                }))));
+
+            return None;
         } else if segment.starts_with("if") {
             let reversed;
             let condition;
@@ -606,6 +1220,7 @@ impl Tokenizer {
             let body = Arc::new(RwLock::new(Vec::new()));
             let token = Token::If(IfToken {
                 reversed,
+                binding: None,
                 condition: Arc::clone(&condition),
                 body: Arc::clone(&body),
             });
@@ -614,6 +1229,7 @@ impl Tokenizer {
             self.inside
                 .push(Arc::new(Mutex::new(InsideToken::If(IfToken {
                     reversed,
+                    binding: None,
                     condition,
                     body,
                 }))));
@@ -635,6 +1251,16 @@ impl Tokenizer {
             }
         }
 
+        if let Some(name) = Self::host_fn_name(segment) {
+            let tokens = self.parse_args(&segment[name.len() + 1..segment.len() - 1]);
+
+            return Some(Token::FnCall(FnCallToken {
+                name: name.to_string(),
+                args: tokens.into_iter().map(Arc::new).collect(),
+                location: self.location(),
+            }));
+        }
+
         let parts = segment.splitn(2, "(").collect::<Vec<&str>>()[0]
             .splitn(3, ".")
             .collect::<Vec<&str>>();
@@ -676,11 +1302,13 @@ impl Tokenizer {
                             let fn_name = segment[let_token.name.len() + 2..]
                                 .split("(")
                                 .collect::<Vec<&str>>()[0];
+                            let tokens = self.parse_static_args(segment, let_token, fn_name);
 
                             return Some(Token::StaticClassFnCall(StaticClassFnCallToken {
                                 name: fn_name.to_string(),
                                 class: let_token.name.clone(),
-                                args: Vec::new(),
+                                args: tokens.into_iter().map(Arc::new).collect(),
+                                location: self.location(),
                             }));
                         } else if segment.starts_with(&format!("{}(", let_token.name)) {
                             let tokens = self
@@ -704,6 +1332,7 @@ impl Tokenizer {
                                 name: parts[1].to_string(),
                                 instance: parts[0].to_string(),
                                 args: tokens.into_iter().map(Arc::new).collect(),
+                                location: self.location(),
                             }));
                         }
                     }
@@ -715,6 +1344,18 @@ impl Tokenizer {
                 };
 
                 if let_token.is_const {
+                    if ["=", "+=", "-=", "*=", "/="]
+                        .iter()
+                        .any(|op| segment.starts_with(&format!("{} {op} ", let_token.name)))
+                        || segment == format!("{}++", let_token.name)
+                        || segment == format!("{}--", let_token.name)
+                    {
+                        panic!(
+                            "cannot assign to const '{}' in {}",
+                            let_token.name, self.location
+                        );
+                    }
+
                     continue;
                 }
 
@@ -800,14 +1441,64 @@ impl Tokenizer {
     }
 
     pub fn parse_expression(&self, segment: &str) -> Option<ExpressionToken> {
-        if segment.starts_with("\"") && segment.ends_with("\"") {
+        if let Some(index) = Self::find_top_level(segment, " ?? ") {
+            let left = self.parse_expression(segment[..index].trim());
+            let right = self.parse_expression(segment[index + 4..].trim());
+
+            if left.is_none() || right.is_none() {
+                panic!("unexpected value in {} (did you typo?)", self.location);
+            }
+
+            return Some(ExpressionToken::Coalesce(CoalesceToken {
+                left: Arc::new(left.unwrap()),
+                right: Arc::new(right.unwrap()),
+            }));
+        }
+
+        // user-defined infix operators, left-associative, earlier definitions binding looser
+        for (symbol, function) in &self.operators {
+            let needle = format!(" {symbol} ");
+
+            let mut index = None;
+            let mut offset = 0;
+            while let Some(found) = Self::find_top_level(&segment[offset..], &needle) {
+                index = Some(offset + found);
+                offset += found + needle.len();
+            }
+
+            if let Some(index) = index {
+                let left = self.parse_expression(segment[..index].trim());
+                let right = self.parse_expression(segment[index + needle.len()..].trim());
+
+                if left.is_none() || right.is_none() {
+                    panic!("unexpected value in {} (did you typo?)", self.location);
+                }
+
+                return Some(ExpressionToken::FnCall(FnCallToken {
+                    name: function.clone(),
+                    args: vec![Arc::new(left.unwrap()), Arc::new(right.unwrap())],
+                    location: self.location(),
+                }));
+            }
+        }
+
+        // a literal can't contain quotes, so `"a" == "b"` falls through to comparisons
+        if segment.len() >= 2
+            && segment.starts_with("\"")
+            && segment.ends_with("\"")
+            && !segment[1..segment.len() - 1].contains('"')
+        {
+            let content = &segment[1..segment.len() - 1];
+
+            if let Some(parts) = Self::parse_interpolation(content) {
+                return Some(ExpressionToken::Interpolation(InterpolationToken {
+                    parts,
+                    location: self.location(),
+                }));
+            }
+
             return Some(ExpressionToken::Value(ValueToken::String(StringToken {
-                value: segment[1..segment.len() - 1]
-                    .to_string()
-                    .replace("\\n", "\n")
-                    .replace("\\r", "\r")
-                    .replace("\\t", "\t")
-                    .replace("\\\\", "\\"),
+                value: Self::unescape(content),
                 location: self.location(),
             })));
         } else if segment.starts_with("[") && segment.ends_with("]") {
@@ -906,6 +1597,16 @@ impl Tokenizer {
             }
         }
 
+        if let Some(name) = Self::host_fn_name(segment) {
+            let tokens = self.parse_args(&segment[name.len() + 1..segment.len() - 1]);
+
+            return Some(ExpressionToken::FnCall(FnCallToken {
+                name: name.to_string(),
+                args: tokens.into_iter().map(Arc::new).collect(),
+                location: self.location(),
+            }));
+        }
+
         for (name, func) in &self.default_macros {
             if segment.starts_with(format!("{name}(").as_str()) && segment.ends_with(")") {
                 let tokens = self.parse_args(&segment[name.len() + 1..segment.len() - 1]);
@@ -928,6 +1629,8 @@ impl Tokenizer {
                             is_function: false,
                             is_class: false,
                             value: Arc::new(RwLock::new(token)),
+
+                            location: self.location(),
                         }));
                     }
                 }
@@ -941,6 +1644,8 @@ impl Tokenizer {
                             is_function: false,
                             is_class: false,
                             value: Arc::new(RwLock::new(token)),
+
+                            location: self.location(),
                         }));
                     }
                 }
@@ -957,12 +1662,14 @@ impl Tokenizer {
                             let fn_name = segment[let_token.name.len() + 2..]
                                 .split("(")
                                 .collect::<Vec<&str>>()[0];
+                            let tokens = self.parse_static_args(segment, let_token, fn_name);
 
                             return Some(ExpressionToken::StaticClassFnCall(
                                 StaticClassFnCallToken {
                                     name: fn_name.to_string(),
                                     class: let_token.name.clone(),
-                                    args: Vec::new(),
+                                    args: tokens.into_iter().map(Arc::new).collect(),
+                                    location: self.location(),
                                 },
                             ));
                         } else if segment.starts_with(&format!("{}(", let_token.name)) {
@@ -987,15 +1694,22 @@ impl Tokenizer {
                                 name: parts[1].to_string(),
                                 instance: parts[0].to_string(),
                                 args: tokens.into_iter().map(Arc::new).collect(),
+                                location: self.location(),
+                            }));
+                        } else if segment == format!("{}.{}", let_token.name, parts[1])
+                            && matches!(
+                                &*let_token.value.read().unwrap(),
+                                ExpressionToken::Value(ValueToken::Namespace(_))
+                            )
+                        {
+                            return Some(ExpressionToken::NamespaceMember(NamespaceMemberToken {
+                                namespace: parts[0].to_string(),
+                                member: parts[1].to_string(),
                             }));
                         }
                     }
                     // get a class property
-                    3 => {
-                        if parts[1] != "#" {
-                            panic!("unexpected expression in {} (did you typo?)", self.location);
-                        }
-
+                    3 if parts[1] == "#" => {
                         let property = parts[2];
 
                         if let ExpressionToken::Value(ValueToken::Class(class_token)) =
@@ -1012,6 +1726,8 @@ impl Tokenizer {
                                         is_function: let_token.is_function,
                                         is_class: let_token.is_class,
                                         value: Arc::clone(&let_token.value),
+
+                                        location: self.location(),
                                     }));
                                 }
                             }
@@ -1033,11 +1749,29 @@ impl Tokenizer {
                             ExpressionToken::Value(ValueToken::Class(_))
                         ),
                         value: Arc::clone(&let_token.value),
+
+                        location: self.location(),
                     }));
                 }
             }
         }
 
+        // chained property access (a.b.c)
+        {
+            let path = segment.split(".").collect::<Vec<&str>>();
+            if path.len() > 1
+                && path.iter().all(|part| {
+                    !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+                })
+                && let Some(base) = self.parse_expression(path[0])
+            {
+                return Some(ExpressionToken::PropertyAccess(PropertyAccessToken {
+                    base: Arc::new(base),
+                    path: path[1..].iter().map(|part| part.to_string()).collect(),
+                }));
+            }
+        }
+
         // comparison parsing
         {
             let mut left = String::new();
@@ -1045,8 +1779,9 @@ impl Tokenizer {
             let mut operator = None;
 
             let mut on_left = true;
+            let mut in_string = false;
             for c in segment.chars() {
-                if on_left && left.ends_with(" ") {
+                if on_left && !in_string && left.ends_with(" ") {
                     left.pop();
 
                     for o in COMPARISON_OPERATORS {
@@ -1063,6 +1798,10 @@ impl Tokenizer {
                     }
                 }
 
+                if c == '"' {
+                    in_string = !in_string;
+                }
+
                 if on_left {
                     left.push(c);
                 } else {
@@ -1096,7 +1835,13 @@ impl Tokenizer {
                 }
             }
 
-            if let Ok(expression) = meval::Expr::from_str(segment) {
+            let segment = if segment.contains('%') {
+                Self::rewrite_modulo(segment)
+            } else {
+                segment.to_string()
+            };
+
+            if let Ok(expression) = meval::Expr::from_str(&segment) {
                 return Some(ExpressionToken::Math(expression));
             }
         }
@@ -1146,6 +1891,72 @@ impl Tokenizer {
         tokens
     }
 
+    fn resolve_path(&self, file: &str) -> String {
+        let relative = std::path::Path::new(&self.location.file)
+            .parent()
+            .map(|parent| parent.join(file));
+
+        match relative {
+            Some(relative) if relative.exists() => relative.to_string_lossy().to_string(),
+            _ => file.to_string(),
+        }
+    }
+
+    fn canonical_path(file: &str) -> String {
+        std::fs::canonicalize(file)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or(file.to_string())
+    }
+
+    fn tokenize_file(&self, input: &str, file: &str) -> Tokenizer {
+        let path = Self::canonical_path(file);
+
+        if self.including.contains(&path) {
+            panic!(
+                "include cycle detected in {}: {} -> {}",
+                self.location,
+                self.including.join(" -> "),
+                path
+            );
+        }
+
+        let mut tokenizer = Tokenizer::new(input, file);
+        tokenizer.including = self.including.clone();
+        tokenizer.including.push(path);
+        tokenizer.parse();
+
+        tokenizer
+    }
+
+    fn parse_static_args(
+        &self,
+        segment: &str,
+        let_token: &LetToken,
+        fn_name: &str,
+    ) -> Vec<ExpressionToken> {
+        let start = let_token.name.len() + 2 + fn_name.len();
+
+        if segment[start..].starts_with("(") && segment.ends_with(")") {
+            self.parse_args(&segment[start + 1..segment.len() - 1])
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn check_include_collision(&self, let_token: &LetToken) {
+        // redeclaring a top-level variable fails at runtime, so report it while tokenizing
+        for token in &self.tokens {
+            if let Token::Let(existing) = token
+                && existing.name == let_token.name
+            {
+                panic!(
+                    "'{}' from {} is already declared at {} (included in {})",
+                    let_token.name, let_token.location, existing.location, self.location
+                );
+            }
+        }
+    }
+
     fn location(&self) -> TokenLocation {
         self.location.clone()
     }
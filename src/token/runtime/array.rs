@@ -3,18 +3,24 @@ use crate::{
     token::{
         TokenLocation,
         base::{
-            ArrayToken, BaseToken, BooleanToken, NullToken, NumberToken, StringToken, ValueToken,
+            ArrayToken, BaseToken, BooleanToken, FunctionToken, NullToken, NumberToken,
+            StringToken, ValueToken,
         },
         logic::ExpressionToken,
     },
 };
 
+use super::invoke;
+
 use std::sync::{Arc, LazyLock, RwLock};
 
 pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
     vec![
         "array#push",
         "array#pop",
+        "array#push_front",
+        "array#pop_front",
+        "array#splice",
         "array#len",
         "array#clone",
         "array#concat",
@@ -22,9 +28,93 @@ pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
         "array#from",
         "array#get",
         "array#set",
+        "array#join",
+        "array#enumerate",
+        "array#chunk",
+        "array#flat_map",
+        "array#any",
+        "array#all",
+        "array#find_index",
+        "array#take",
+        "array#drop",
+        "array#take_while",
+        "array#drop_while",
+        "array#sort_by",
+        "array#group_by",
+        "array#reduce_while",
     ]
 });
 
+/// Stable merge sort driven by a user comparator. `slice::sort_by` is avoided because
+/// it may panic when a comparator doesn't implement a total order.
+fn sort_by(
+    runtime: &mut Runtime,
+    function: &FunctionToken,
+    items: Vec<ValueToken>,
+    location: &TokenLocation,
+) -> Vec<ValueToken> {
+    if items.len() <= 1 {
+        return items;
+    }
+
+    let mut left = items;
+    let right = left.split_off(left.len() / 2);
+    let left = sort_by(runtime, function, left, location);
+    let right = sort_by(runtime, function, right, location);
+
+    let mut sorted = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        let order = match invoke(runtime, function, vec![a.clone(), b.clone()]) {
+            ValueToken::Number(number) => number.value,
+            _ => panic!("array#sort_by requires the comparator to return a number in {location}"),
+        };
+
+        // take from the right only when it sorts strictly first, keeping equal items in order
+        if order > 0.0 {
+            sorted.push(right.next().unwrap());
+        } else {
+            sorted.push(left.next().unwrap());
+        }
+    }
+
+    sorted.extend(left);
+    sorted.extend(right);
+    sorted
+}
+
+pub fn join(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<String> {
+    if args.len() != 2 {
+        panic!("{name} requires 2 arguments in {location}");
+    }
+
+    let value = runtime.extract_value(&args[0])?;
+    let separator = runtime.extract_value(&args[1])?.value(0);
+
+    match value {
+        ValueToken::Array(array) => {
+            let items = array.value.read().unwrap().clone();
+            let mut parts = Vec::with_capacity(items.len());
+
+            for item in items.iter() {
+                parts.push(runtime.extract_value(item)?.value(0));
+            }
+
+            Some(parts.join(&separator))
+        }
+        _ => {
+            panic!("{name} requires an array as the first argument in {location}");
+        }
+    }
+}
+
 pub fn run(
     name: &str,
     args: &[Arc<ExpressionToken>],
@@ -81,6 +171,104 @@ pub fn run(
                 }
             }
         }
+        "array#push_front" => {
+            if args.len() < 2 {
+                panic!("array#push_front requires at least 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::Array(array) => {
+                    // insert in argument order, so push_front(a, 1, 2) starts with 1, 2
+                    for (index, arg) in args.iter().skip(1).enumerate() {
+                        let value = runtime.extract_value(arg)?;
+                        array
+                            .value
+                            .write()
+                            .unwrap()
+                            .insert(index, ExpressionToken::Value(value));
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Array(array.clone())))
+                }
+                _ => {
+                    panic!(
+                        "array#push_front requires an array as the first argument in {location}"
+                    );
+                }
+            }
+        }
+        "array#pop_front" => {
+            if args.len() != 1 {
+                panic!("array#pop_front requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::Array(array) => {
+                    let mut array = array.value.write().unwrap();
+
+                    if array.is_empty() {
+                        return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                            location: Default::default(),
+                        })));
+                    }
+
+                    Some(array.remove(0))
+                }
+                _ => {
+                    panic!("array#pop_front requires an array as the first argument in {location}");
+                }
+            }
+        }
+        "array#splice" => {
+            if args.len() < 3 {
+                panic!("array#splice requires at least 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let start = runtime.extract_value(&args[1])?;
+            let count = runtime.extract_value(&args[2])?;
+            match (value, start, count) {
+                (
+                    ValueToken::Array(array),
+                    ValueToken::Number(start),
+                    ValueToken::Number(count),
+                ) => {
+                    let mut inserted = Vec::new();
+                    for arg in args.iter().skip(3) {
+                        inserted.push(ExpressionToken::Value(runtime.extract_value(arg)?));
+                    }
+
+                    let removed = {
+                        let mut array = array.value.write().unwrap();
+                        // like js, a negative start counts back from the end
+                        let start = if start.value < 0.0 {
+                            (array.len() as f64 + start.value.trunc()).max(0.0) as usize
+                        } else {
+                            (start.value as usize).min(array.len())
+                        };
+                        let count = super::to_index(&count).unwrap_or(0);
+                        let end = start + count.min(array.len() - start);
+
+                        array.splice(start..end, inserted).collect::<Vec<_>>()
+                    };
+
+                    let removed = removed
+                        .iter()
+                        .map(|item| ExpressionToken::Value(runtime.extract_value(item).unwrap()))
+                        .collect();
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(removed)),
+                    })))
+                }
+                _ => {
+                    panic!("array#splice requires an array and 2 numbers in {location}");
+                }
+            }
+        }
         "array#len" => {
             if args.len() != 1 {
                 panic!("array#len requires 1 argument in {location}");
@@ -287,9 +475,9 @@ pub fn run(
                     let index = runtime.extract_value(&args[1])?;
                     match index {
                         ValueToken::Number(number) => {
-                            let index = number.value as usize;
-                            let value =
-                                array.value.read().unwrap().get(index).cloned().unwrap_or({
+                            let value = super::to_index(&number)
+                                .and_then(|index| array.value.read().unwrap().get(index).cloned())
+                                .unwrap_or({
                                     ExpressionToken::Value(ValueToken::Null(NullToken {
                                         location: Default::default(),
                                     }))
@@ -316,18 +504,20 @@ pub fn run(
                         let index = runtime.extract_value(&args[1])?;
                         match index {
                             ValueToken::Number(number) => {
-                                let index = number.value as usize;
-                                let value = start + index;
+                                let value = super::to_index(&number).map(|index| start + index);
 
-                                if value > end {
-                                    Some(ExpressionToken::Value(ValueToken::Null(NullToken {
-                                        location: Default::default(),
-                                    })))
-                                } else {
-                                    Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
-                                        location: Default::default(),
-                                        value: value as f64,
-                                    })))
+                                match value {
+                                    Some(value) if value <= end => Some(ExpressionToken::Value(
+                                        ValueToken::Number(NumberToken {
+                                            location: Default::default(),
+                                            value: value as f64,
+                                        }),
+                                    )),
+                                    _ => {
+                                        Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                                            location: Default::default(),
+                                        })))
+                                    }
                                 }
                             }
                             _ => {
@@ -346,11 +536,8 @@ pub fn run(
                     let index = runtime.extract_value(&args[1])?;
                     match index {
                         ValueToken::Number(number) => {
-                            let index = number.value as usize;
-                            let value = string
-                                .value
-                                .chars()
-                                .nth(index)
+                            let value = super::to_index(&number)
+                                .and_then(|index| string.value.chars().nth(index))
                                 .map(|c| {
                                     ExpressionToken::Value(ValueToken::String(StringToken {
                                         location: Default::default(),
@@ -376,7 +563,13 @@ pub fn run(
                     let index = runtime.extract_value(&args[1])?;
                     match index {
                         ValueToken::Number(number) => {
-                            let index = number.value as usize;
+                            // numbers have 64 bits to read
+                            let Some(index) = super::to_index(&number).filter(|index| *index < 64)
+                            else {
+                                return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                                    location: Default::default(),
+                                })));
+                            };
 
                             let integer = num.value as u64;
                             let value = (integer >> index) & 1;
@@ -413,7 +606,12 @@ pub fn run(
 
                     match index {
                         ValueToken::Number(number) => {
-                            let index = number.value as usize;
+                            let Some(index) = super::to_index(&number) else {
+                                panic!(
+                                    "array#set requires a non-negative index, got {} in {location}",
+                                    number.value
+                                );
+                            };
                             let mut arr = array.value.write().unwrap();
 
                             if index >= arr.len() {
@@ -441,7 +639,13 @@ pub fn run(
 
                     match index {
                         ValueToken::Number(number) => {
-                            let index = number.value as usize;
+                            let Some(index) = super::to_index(&number).filter(|index| *index < 64)
+                            else {
+                                panic!(
+                                    "array#set requires a bit index from 0 to 63, got {} in {location}",
+                                    number.value
+                                );
+                            };
 
                             let integer = num.value as u64;
                             let bit = runtime.extract_value(&args[2])?;
@@ -480,6 +684,391 @@ pub fn run(
                 }
             }
         }
+        "array#join" => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+            location: Default::default(),
+            value: join(name, args, runtime, location)?,
+        }))),
+        "array#enumerate" => {
+            if args.len() != 1 {
+                panic!("array#enumerate requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::Array(array) => {
+                    let value = array.value.read().unwrap().clone();
+                    let mut new_value = Vec::new();
+
+                    for (i, item) in value.iter().enumerate() {
+                        new_value.push(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                            location: Default::default(),
+                            value: Arc::new(RwLock::new(vec![
+                                ExpressionToken::Value(ValueToken::Number(NumberToken {
+                                    location: Default::default(),
+                                    value: i as f64,
+                                })),
+                                ExpressionToken::Value(runtime.extract_value(item).unwrap()),
+                            ])),
+                        })));
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(new_value)),
+                    })))
+                }
+                _ => {
+                    panic!("array#enumerate requires an array as the first argument in {location}");
+                }
+            }
+        }
+        "array#chunk" => {
+            if args.len() != 2 {
+                panic!("array#chunk requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let size = runtime.extract_value(&args[1])?;
+            match (value, size) {
+                (ValueToken::Array(array), ValueToken::Number(size)) => {
+                    let size = size.value as usize;
+                    if size == 0 {
+                        panic!("array#chunk requires a size greater than 0 in {location}");
+                    }
+
+                    let value = array.value.read().unwrap().clone();
+                    let mut new_value = Vec::new();
+
+                    for chunk in value.chunks(size) {
+                        let mut items = Vec::new();
+
+                        for item in chunk.iter() {
+                            items
+                                .push(ExpressionToken::Value(runtime.extract_value(item).unwrap()));
+                        }
+
+                        new_value.push(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                            location: Default::default(),
+                            value: Arc::new(RwLock::new(items)),
+                        })));
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(new_value)),
+                    })))
+                }
+                _ => {
+                    panic!("array#chunk requires an array and a number in {location}");
+                }
+            }
+        }
+        "array#flat_map" => {
+            if args.len() != 2 {
+                panic!("array#flat_map requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+                    let mut new_value = Vec::new();
+
+                    for item in value.iter() {
+                        let item = runtime.extract_value(item).unwrap();
+
+                        match invoke(runtime, &function, vec![item]) {
+                            ValueToken::Array(result) => {
+                                let result = result.value.read().unwrap().clone();
+
+                                for item in result.iter() {
+                                    new_value.push(ExpressionToken::Value(
+                                        runtime.extract_value(item).unwrap(),
+                                    ));
+                                }
+                            }
+                            result => new_value.push(ExpressionToken::Value(result)),
+                        }
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(new_value)),
+                    })))
+                }
+                _ => {
+                    panic!("array#flat_map requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#any" | "array#all" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+                    // any stops at the first truthy result, all at the first falsy one
+                    let stop_on = name == "array#any";
+
+                    for item in value.iter() {
+                        let item = runtime.extract_value(item).unwrap();
+
+                        if invoke(runtime, &function, vec![item]).truthy() == stop_on {
+                            return Some(ExpressionToken::Value(ValueToken::Boolean(
+                                BooleanToken {
+                                    location: Default::default(),
+                                    value: stop_on,
+                                },
+                            )));
+                        }
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                        location: Default::default(),
+                        value: !stop_on,
+                    })))
+                }
+                _ => {
+                    panic!("{name} requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#find_index" => {
+            if args.len() != 2 {
+                panic!("array#find_index requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+
+                    for (index, item) in value.iter().enumerate() {
+                        let item = runtime.extract_value(item).unwrap();
+
+                        if invoke(runtime, &function, vec![item]).truthy() {
+                            return Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                                location: Default::default(),
+                                value: index as f64,
+                            })));
+                        }
+                    }
+
+                    Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                        location: Default::default(),
+                    })))
+                }
+                _ => {
+                    panic!("array#find_index requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#take" | "array#drop" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let count = runtime.extract_value(&args[1])?;
+            match (value, count) {
+                (ValueToken::Array(array), ValueToken::Number(count)) => {
+                    let Some(count) = super::to_index(&count) else {
+                        panic!("{name} requires a non-negative count in {location}");
+                    };
+
+                    let value = array.value.read().unwrap().clone();
+                    let (taken, dropped) = value.split_at(count.min(value.len()));
+                    let items = if name == "array#take" { taken } else { dropped };
+
+                    let items = items
+                        .iter()
+                        .map(|item| ExpressionToken::Value(runtime.extract_value(item).unwrap()))
+                        .collect();
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(items)),
+                    })))
+                }
+                _ => {
+                    panic!("{name} requires an array and a number in {location}");
+                }
+            }
+        }
+        "array#take_while" | "array#drop_while" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+                    let items = value
+                        .iter()
+                        .map(|item| runtime.extract_value(item).unwrap())
+                        .collect::<Vec<_>>();
+
+                    let mut split = items.len();
+                    for (index, item) in items.iter().enumerate() {
+                        if !invoke(runtime, &function, vec![item.clone()]).truthy() {
+                            split = index;
+                            break;
+                        }
+                    }
+
+                    let (taken, dropped) = items.split_at(split);
+                    let items = if name == "array#take_while" {
+                        taken
+                    } else {
+                        dropped
+                    };
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(
+                            items.iter().cloned().map(ExpressionToken::Value).collect(),
+                        )),
+                    })))
+                }
+                _ => {
+                    panic!("{name} requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#sort_by" => {
+            if args.len() != 2 {
+                panic!("array#sort_by requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+                    let items = value
+                        .iter()
+                        .map(|item| runtime.extract_value(item).unwrap())
+                        .collect();
+
+                    let sorted = sort_by(runtime, &function, items, location);
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(
+                            sorted.into_iter().map(ExpressionToken::Value).collect(),
+                        )),
+                    })))
+                }
+                _ => {
+                    panic!("array#sort_by requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#group_by" => {
+            if args.len() != 2 {
+                panic!("array#group_by requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+                    // there is no map type, so groups are [key, items] pairs in first-seen order
+                    let mut groups: Vec<(String, Vec<ExpressionToken>)> = Vec::new();
+
+                    for item in value.iter() {
+                        let item = runtime.extract_value(item).unwrap();
+                        let key = invoke(runtime, &function, vec![item.clone()]).value(0);
+
+                        match groups.iter_mut().find(|(group, _)| *group == key) {
+                            Some((_, items)) => items.push(ExpressionToken::Value(item)),
+                            None => groups.push((key, vec![ExpressionToken::Value(item)])),
+                        }
+                    }
+
+                    let groups = groups
+                        .into_iter()
+                        .map(|(key, items)| {
+                            ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                                location: Default::default(),
+                                value: Arc::new(RwLock::new(vec![
+                                    ExpressionToken::Value(ValueToken::String(StringToken {
+                                        location: Default::default(),
+                                        value: key,
+                                    })),
+                                    ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                                        location: Default::default(),
+                                        value: Arc::new(RwLock::new(items)),
+                                    })),
+                                ])),
+                            }))
+                        })
+                        .collect();
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(groups)),
+                    })))
+                }
+                _ => {
+                    panic!("array#group_by requires an array and a function in {location}");
+                }
+            }
+        }
+        "array#reduce_while" => {
+            if args.len() != 3 {
+                panic!("array#reduce_while requires 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let mut accumulator = runtime.extract_value(&args[1])?;
+            let function = runtime.extract_value(&args[2])?;
+            match (value, function) {
+                (ValueToken::Array(array), ValueToken::Function(function)) => {
+                    let value = array.value.read().unwrap().clone();
+
+                    for item in value.iter() {
+                        let item = runtime.extract_value(item).unwrap();
+
+                        let ValueToken::Array(result) =
+                            invoke(runtime, &function, vec![accumulator, item])
+                        else {
+                            panic!(
+                                "array#reduce_while requires the reducer to return [continue, accumulator] in {location}"
+                            );
+                        };
+
+                        let result = result.value.read().unwrap().clone();
+                        let [proceed, next] = result.as_slice() else {
+                            panic!(
+                                "array#reduce_while requires the reducer to return [continue, accumulator] in {location}"
+                            );
+                        };
+
+                        accumulator = runtime.extract_value(next).unwrap();
+                        if !runtime.extract_value(proceed).unwrap().truthy() {
+                            break;
+                        }
+                    }
+
+                    Some(ExpressionToken::Value(accumulator))
+                }
+                _ => {
+                    panic!(
+                        "array#reduce_while requires an array, an initial value and a function in {location}"
+                    );
+                }
+            }
+        }
         _ => None,
     }
 }
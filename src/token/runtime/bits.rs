@@ -0,0 +1,70 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{NumberToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::sync::{Arc, LazyLock};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "bits#and", "bits#or", "bits#xor", "bits#shl", "bits#shr", "bits#not",
+    ]
+});
+
+/// Casts a number operand to `u64`, going through `i64` so negative numbers keep
+/// their two's complement bits.
+fn extract_bits(
+    arg: &ExpressionToken,
+    runtime: &mut Runtime,
+    name: &str,
+    location: &TokenLocation,
+) -> Option<u64> {
+    match runtime.extract_value(arg)? {
+        ValueToken::Number(number) => Some(number.value as i64 as u64),
+        _ => panic!("{name} requires numbers in {location}"),
+    }
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    let value = match name {
+        "bits#not" => {
+            if args.len() != 1 {
+                panic!("bits#not requires 1 argument in {location}");
+            }
+
+            !extract_bits(&args[0], runtime, name, location)?
+        }
+        "bits#and" | "bits#or" | "bits#xor" | "bits#shl" | "bits#shr" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let left = extract_bits(&args[0], runtime, name, location)?;
+            let right = extract_bits(&args[1], runtime, name, location)?;
+
+            match name {
+                "bits#and" => left & right,
+                "bits#or" => left | right,
+                "bits#xor" => left ^ right,
+                // shifting by the width or more clears every bit
+                "bits#shl" => left.checked_shl(right as u32).unwrap_or(0),
+                _ => left.checked_shr(right as u32).unwrap_or(0),
+            }
+        }
+        _ => return None,
+    };
+
+    Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+        location: Default::default(),
+        value: value as f64,
+    })))
+}
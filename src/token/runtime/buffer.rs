@@ -0,0 +1,272 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{BooleanToken, BufferToken, NullToken, NumberToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "buffer#slice",
+        "buffer#to_hex",
+        "buffer#equals",
+        "buffer#equals_ct",
+        "buffer#read_u16",
+        "buffer#read_u32",
+        "buffer#read_i16",
+        "buffer#read_i32",
+        "buffer#read_u16_le",
+        "buffer#read_u32_le",
+        "buffer#read_i16_le",
+        "buffer#read_i32_le",
+        "buffer#write_u16",
+        "buffer#write_u32",
+        "buffer#write_i16",
+        "buffer#write_i32",
+        "buffer#write_u16_le",
+        "buffer#write_u32_le",
+        "buffer#write_i16_le",
+        "buffer#write_i32_le",
+    ]
+});
+
+/// Integer layout encoded in a read/write function name, e.g. `buffer#read_i16_le`.
+struct Layout {
+    width: usize,
+    signed: bool,
+    little_endian: bool,
+}
+
+impl Layout {
+    fn from_name(name: &str) -> Self {
+        let kind = name
+            .trim_start_matches("buffer#read_")
+            .trim_start_matches("buffer#write_");
+        let (kind, little_endian) = match kind.strip_suffix("_le") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+
+        Self {
+            width: if kind.ends_with("16") { 2 } else { 4 },
+            signed: kind.starts_with('i'),
+            little_endian,
+        }
+    }
+
+    fn read(&self, bytes: &[u8]) -> f64 {
+        let mut raw = [0u8; 4];
+        if self.little_endian {
+            raw[..self.width].copy_from_slice(bytes);
+        } else {
+            for (i, byte) in bytes.iter().rev().enumerate() {
+                raw[i] = *byte;
+            }
+        }
+
+        let value = u32::from_le_bytes(raw);
+        match (self.width, self.signed) {
+            (2, true) => value as u16 as i16 as f64,
+            (2, false) => value as u16 as f64,
+            (_, true) => value as i32 as f64,
+            (_, false) => value as f64,
+        }
+    }
+
+    fn write(&self, value: f64) -> Vec<u8> {
+        let raw = if self.signed {
+            (value as i64 as u32).to_le_bytes()
+        } else {
+            (value as u32).to_le_bytes()
+        };
+
+        let mut bytes = raw[..self.width].to_vec();
+        if !self.little_endian {
+            bytes.reverse();
+        }
+
+        bytes
+    }
+}
+
+/// Returns the buffer and its offset, which is `None` for negative, NaN or too large
+/// numbers.
+fn extract_offset(
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    name: &str,
+    location: &TokenLocation,
+) -> Option<(BufferToken, Option<usize>)> {
+    let buffer = match runtime.extract_value(&args[0])? {
+        ValueToken::Buffer(buffer) => buffer,
+        _ => panic!("{name} requires a buffer as the first argument in {location}"),
+    };
+    let offset = match runtime.extract_value(&args[1])? {
+        ValueToken::Number(number) => super::to_index(&number),
+        _ => panic!("{name} requires a number as the second argument in {location}"),
+    };
+
+    Some((buffer, offset))
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "buffer#slice" => {
+            if args.len() != 3 {
+                panic!("buffer#slice requires 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let buffer = match value {
+                ValueToken::Buffer(buffer) => buffer,
+                _ => panic!("buffer#slice requires a buffer as the first argument in {location}"),
+            };
+
+            let start = match runtime.extract_value(&args[1])? {
+                ValueToken::Number(number) => super::to_index(&number),
+                _ => panic!("buffer#slice requires a number as the second argument in {location}"),
+            };
+            let end = match runtime.extract_value(&args[2])? {
+                ValueToken::Number(number) => super::to_index(&number),
+                _ => panic!("buffer#slice requires a number as the third argument in {location}"),
+            };
+
+            let (Some(start), Some(end)) = (start, end) else {
+                return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                })));
+            };
+
+            let data = buffer.value.read().unwrap();
+            let end = end.min(data.len());
+            let start = start.min(end);
+
+            Some(ExpressionToken::Value(ValueToken::Buffer(BufferToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(data[start..end].to_vec())),
+            })))
+        }
+        "buffer#to_hex" => {
+            if args.len() != 1 {
+                panic!("buffer#to_hex requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let buffer = match value {
+                ValueToken::Buffer(buffer) => buffer,
+                _ => panic!("buffer#to_hex requires a buffer in {location}"),
+            };
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: buffer
+                    .value
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect(),
+            })))
+        }
+        "buffer#equals" | "buffer#equals_ct" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let (ValueToken::Buffer(left), ValueToken::Buffer(right)) = (
+                runtime.extract_value(&args[0])?,
+                runtime.extract_value(&args[1])?,
+            ) else {
+                panic!("{name} requires 2 buffers in {location}");
+            };
+
+            let value = if name == "buffer#equals" {
+                left == right
+            } else {
+                let left = left.value.read().unwrap().clone();
+                let right = right.value.read().unwrap().clone();
+
+                // accumulate every difference instead of returning at the first mismatch
+                let mut difference = (left.len() != right.len()) as u8;
+                for i in 0..left.len().max(right.len()) {
+                    difference |=
+                        left.get(i).copied().unwrap_or(0) ^ right.get(i).copied().unwrap_or(0);
+                }
+
+                difference == 0
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value,
+            })))
+        }
+        _ if name.starts_with("buffer#read_") => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let (buffer, offset) = extract_offset(args, runtime, name, location)?;
+            let layout = Layout::from_name(name);
+            let data = buffer.value.read().unwrap();
+            let bytes = offset.and_then(|offset| {
+                let end = offset.checked_add(layout.width)?;
+                data.get(offset..end)
+            });
+
+            match bytes {
+                Some(bytes) => Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                    location: Default::default(),
+                    value: layout.read(bytes),
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        _ if name.starts_with("buffer#write_") => {
+            if args.len() != 3 {
+                panic!("{name} requires 3 arguments in {location}");
+            }
+
+            let (buffer, offset) = extract_offset(args, runtime, name, location)?;
+            let value = match runtime.extract_value(&args[2])? {
+                ValueToken::Number(number) => number.value,
+                _ => panic!("{name} requires a number as the third argument in {location}"),
+            };
+
+            let layout = Layout::from_name(name);
+            let bytes = layout.write(value);
+
+            {
+                let mut data = buffer.value.write().unwrap();
+
+                // writes may grow the buffer from its end, but not leave a gap
+                let Some(offset) = offset.filter(|offset| *offset <= data.len()) else {
+                    panic!(
+                        "{name} requires an offset between 0 and the buffer length in {location}"
+                    );
+                };
+
+                let end = offset + layout.width;
+                if data.len() < end {
+                    data.resize(end, 0);
+                }
+
+                data[offset..end].copy_from_slice(&bytes);
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Buffer(buffer)))
+        }
+        _ => None,
+    }
+}
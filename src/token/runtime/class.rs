@@ -2,14 +2,30 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{StringToken, ValueToken},
+        base::{ArrayToken, ClassInstanceToken, StringToken, ValueToken},
         logic::ExpressionToken,
     },
 };
 
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, RwLock};
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["class#get"]);
+pub static FUNCTIONS: LazyLock<Vec<&str>> =
+    LazyLock::new(|| vec!["class#get", "class#set", "class#keys", "class#fields"]);
+
+/// Returns the instance's fields sorted by name, skipping methods, so that
+/// enumeration order does not depend on the scope's hash order.
+pub fn fields(class_instance: &ClassInstanceToken) -> Vec<(String, ExpressionToken)> {
+    let scope = class_instance.scope.read().unwrap();
+
+    let mut fields: Vec<(String, ExpressionToken)> = scope
+        .iter()
+        .map(|(key, value)| (key.clone(), value.read().unwrap().clone()))
+        .filter(|(_, value)| !matches!(value, ExpressionToken::Value(ValueToken::Function(_))))
+        .collect();
+    fields.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+    fields
+}
 
 pub fn run(
     name: &str,
@@ -20,7 +36,7 @@ pub fn run(
     match name {
         "class#get" => {
             if args.len() != 2 {
-                panic!("array#get requires 2 arguments in {location}");
+                panic!("class#get requires 2 arguments in {location}");
             }
 
             let value = runtime.extract_value(&args[0])?;
@@ -48,6 +64,109 @@ pub fn run(
                 }
             }
         }
+        "class#set" => {
+            if args.len() != 3 {
+                panic!("class#set requires 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::ClassInstance(class_instance) => {
+                    let key = match runtime.extract_value(&args[1])? {
+                        ValueToken::String(StringToken { value, .. }) => value,
+                        _ => {
+                            panic!(
+                                "class#set requires a string as the second argument in {location}"
+                            );
+                        }
+                    };
+                    let value = runtime.extract_value(&args[2])?;
+
+                    class_instance
+                        .scope
+                        .write()
+                        .unwrap()
+                        .insert(key, Arc::new(RwLock::new(ExpressionToken::Value(value))));
+
+                    Some(ExpressionToken::Value(ValueToken::ClassInstance(
+                        class_instance,
+                    )))
+                }
+                _ => {
+                    panic!(
+                        "class#set requires a class instance as the first argument in {location}"
+                    );
+                }
+            }
+        }
+        "class#keys" => {
+            if args.len() != 1 {
+                panic!("class#keys requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::ClassInstance(class_instance) => {
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(
+                            fields(&class_instance)
+                                .into_iter()
+                                .map(|(key, _)| {
+                                    ExpressionToken::Value(ValueToken::String(StringToken {
+                                        location: Default::default(),
+                                        value: key,
+                                    }))
+                                })
+                                .collect(),
+                        )),
+                    })))
+                }
+                _ => {
+                    panic!(
+                        "class#keys requires a class instance as the first argument in {location}"
+                    );
+                }
+            }
+        }
+        "class#fields" => {
+            if args.len() != 1 {
+                panic!("class#fields requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            match value {
+                ValueToken::ClassInstance(class_instance) => {
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(
+                            fields(&class_instance)
+                                .into_iter()
+                                .map(|(key, value)| {
+                                    ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                                        location: Default::default(),
+                                        value: Arc::new(RwLock::new(vec![
+                                            ExpressionToken::Value(ValueToken::String(
+                                                StringToken {
+                                                    location: Default::default(),
+                                                    value: key,
+                                                },
+                                            )),
+                                            value,
+                                        ])),
+                                    }))
+                                })
+                                .collect(),
+                        )),
+                    })))
+                }
+                _ => {
+                    panic!(
+                        "class#fields requires a class instance as the first argument in {location}"
+                    );
+                }
+            }
+        }
         _ => None,
     }
 }
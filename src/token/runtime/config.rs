@@ -0,0 +1,96 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{ArrayToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["config#parse"]);
+
+/// Reads `key = value` lines, skipping blanks and `#`/`;` comments. Keys below an ini
+/// style `[section]` header are prefixed as `section.key`.
+fn parse(input: &str, location: &TokenLocation) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut section = None;
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = Some(name.trim().to_string());
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            panic!(
+                "config#parse expected key=value on line {} in {location}",
+                index + 1
+            );
+        };
+
+        let key = match &section {
+            Some(section) => format!("{section}.{}", key.trim()),
+            None => key.trim().to_string(),
+        };
+
+        pairs.push((key, value.trim().to_string()));
+    }
+
+    pairs
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "config#parse" => {
+            if args.len() != 1 {
+                panic!("config#parse requires 1 argument in {location}");
+            }
+
+            let ValueToken::String(string) = runtime.extract_value(&args[0])? else {
+                panic!("config#parse requires a string in {location}");
+            };
+
+            let pairs = parse(&string.value, location)
+                .into_iter()
+                .map(|(key, value)| {
+                    let pair = [key, value]
+                        .into_iter()
+                        .map(|value| {
+                            ExpressionToken::Value(ValueToken::String(StringToken {
+                                location: Default::default(),
+                                value,
+                            }))
+                        })
+                        .collect();
+
+                    ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(pair)),
+                    }))
+                })
+                .collect();
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(pairs)),
+            })))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{ArrayToken, BaseToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["csv#parse", "csv#stringify"]);
+
+/// Splits csv text into rows of fields. Quoted fields may contain commas, newlines
+/// and `""` escaped quotes; a trailing newline doesn't start another row.
+fn parse(input: &str, location: &TokenLocation) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        panic!("csv#parse found an unterminated quoted field in {location}");
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "csv#parse" => {
+            if args.len() != 1 {
+                panic!("csv#parse requires 1 argument in {location}");
+            }
+
+            let ValueToken::String(string) = runtime.extract_value(&args[0])? else {
+                panic!("csv#parse requires a string in {location}");
+            };
+
+            let rows = parse(&string.value, location)
+                .into_iter()
+                .map(|row| {
+                    let fields = row
+                        .into_iter()
+                        .map(|field| {
+                            ExpressionToken::Value(ValueToken::String(StringToken {
+                                location: Default::default(),
+                                value: field,
+                            }))
+                        })
+                        .collect();
+
+                    ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(fields)),
+                    }))
+                })
+                .collect();
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(rows)),
+            })))
+        }
+        "csv#stringify" => {
+            if args.len() != 1 {
+                panic!("csv#stringify requires 1 argument in {location}");
+            }
+
+            let ValueToken::Array(rows) = runtime.extract_value(&args[0])? else {
+                panic!("csv#stringify requires an array of rows in {location}");
+            };
+
+            let rows = rows.value.read().unwrap().clone();
+            let mut lines = Vec::new();
+
+            for row in rows.iter() {
+                let ValueToken::Array(row) = runtime.extract_value(row)? else {
+                    panic!("csv#stringify requires every row to be an array in {location}");
+                };
+
+                let fields = row.value.read().unwrap().clone();
+                let mut line = Vec::new();
+
+                for field in fields.iter() {
+                    line.push(quote(&runtime.extract_value(field)?.value(0)));
+                }
+
+                lines.push(line.join(","));
+            }
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: lines.join("\n"),
+            })))
+        }
+        _ => None,
+    }
+}
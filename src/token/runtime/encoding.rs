@@ -0,0 +1,242 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{BufferToken, NullToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::sync::{Arc, LazyLock, RwLock};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "encoding#base64_encode",
+        "encoding#base64_decode",
+        "encoding#hex_encode",
+        "encoding#hex_decode",
+        "encoding#url_encode",
+        "encoding#url_decode",
+    ]
+});
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        result.push(BASE64_ALPHABET[(triple >> 18) as usize & 63] as char);
+        result.push(BASE64_ALPHABET[(triple >> 12) as usize & 63] as char);
+
+        if chunk.len() > 1 {
+            result.push(BASE64_ALPHABET[(triple >> 6) as usize & 63] as char);
+        } else {
+            result.push('=');
+        }
+
+        if chunk.len() > 2 {
+            result.push(BASE64_ALPHABET[triple as usize & 63] as char);
+        } else {
+            result.push('=');
+        }
+    }
+
+    result
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=');
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in data.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|b| *b == c)? as u32;
+
+        buffer = (buffer << 6) | value;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+        }
+    }
+
+    if bits >= 6 {
+        return None;
+    }
+
+    Some(result)
+}
+
+fn hex_decode(data: &str) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(2) || !data.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..data.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&data[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn url_encode(data: &str) -> String {
+    let mut result = String::with_capacity(data.len());
+
+    for byte in data.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            result.push(byte as char);
+        } else {
+            result.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    result
+}
+
+fn url_decode(data: &str) -> Option<String> {
+    let bytes = data.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3)?;
+            if !hex.iter().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            result.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(result).ok()
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "encoding#base64_encode" => {
+            if args.len() != 1 {
+                panic!("encoding#base64_encode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = super::extract_bytes(value, name, location);
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: base64_encode(&data),
+            })))
+        }
+        "encoding#base64_decode" => {
+            if args.len() != 1 {
+                panic!("encoding#base64_decode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = match value {
+                ValueToken::String(string) => string.value,
+                _ => panic!("encoding#base64_decode requires a string in {location}"),
+            };
+
+            match base64_decode(&data) {
+                Some(data) => Some(ExpressionToken::Value(ValueToken::Buffer(BufferToken {
+                    location: Default::default(),
+                    value: Arc::new(RwLock::new(data)),
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "encoding#hex_encode" => {
+            if args.len() != 1 {
+                panic!("encoding#hex_encode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = super::extract_bytes(value, name, location);
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: data.iter().map(|byte| format!("{byte:02x}")).collect(),
+            })))
+        }
+        "encoding#hex_decode" => {
+            if args.len() != 1 {
+                panic!("encoding#hex_decode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = match value {
+                ValueToken::String(string) => string.value,
+                _ => panic!("encoding#hex_decode requires a string in {location}"),
+            };
+
+            match hex_decode(&data) {
+                Some(data) => Some(ExpressionToken::Value(ValueToken::Buffer(BufferToken {
+                    location: Default::default(),
+                    value: Arc::new(RwLock::new(data)),
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "encoding#url_encode" => {
+            if args.len() != 1 {
+                panic!("encoding#url_encode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = match value {
+                ValueToken::String(string) => string.value,
+                _ => panic!("encoding#url_encode requires a string in {location}"),
+            };
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: url_encode(&data),
+            })))
+        }
+        "encoding#url_decode" => {
+            if args.len() != 1 {
+                panic!("encoding#url_decode requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let data = match value {
+                ValueToken::String(string) => string.value,
+                _ => panic!("encoding#url_decode requires a string in {location}"),
+            };
+
+            match url_decode(&data) {
+                Some(data) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: data,
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        _ => None,
+    }
+}
@@ -2,18 +2,48 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{BaseToken, BufferToken, StringToken, ValueToken},
+        base::{ArrayToken, BaseToken, BufferToken, NullToken, StringToken, ValueToken},
         logic::ExpressionToken,
     },
 };
 
 use std::{
     io::Read,
+    path::Path,
     sync::{Arc, LazyLock, RwLock},
 };
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> =
-    LazyLock::new(|| vec!["fs#readstr", "fs#readbin", "fs#write"]);
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "fs#readstr",
+        "fs#readbin",
+        "fs#write",
+        "fs#cwd",
+        "fs#chdir",
+        "fs#walk",
+    ]
+});
+
+fn walk(path: &Path, files: &mut Vec<String>) {
+    // directories that can't be read are skipped instead of aborting the walk
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+
+    let mut entries: Vec<_> = entries.filter_map(|entry| entry.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+
+        // `file_type` doesn't follow symlinks, so linked directories are listed, not walked
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            walk(&path, files);
+        } else {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+}
 
 pub fn run(
     name: &str,
@@ -70,6 +100,62 @@ pub fn run(
                 value: "ok".to_string(),
             })))
         }
+        "fs#cwd" => {
+            if !args.is_empty() {
+                panic!("fs#cwd requires no arguments in {location}");
+            }
+
+            let cwd = std::env::current_dir().unwrap();
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: cwd.to_string_lossy().to_string(),
+            })))
+        }
+        "fs#chdir" => {
+            if args.len() != 1 {
+                panic!("fs#chdir requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let path = value.value(0).to_string();
+
+            match std::env::set_current_dir(path) {
+                Ok(_) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: "ok".to_string(),
+                }))),
+                Err(_) => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "fs#walk" => {
+            if args.len() != 1 {
+                panic!("fs#walk requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let path = value.value(0).to_string();
+
+            let mut files = Vec::new();
+            walk(Path::new(&path), &mut files);
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(
+                    files
+                        .into_iter()
+                        .map(|file| {
+                            ExpressionToken::Value(ValueToken::String(StringToken {
+                                location: Default::default(),
+                                value: file,
+                            }))
+                        })
+                        .collect(),
+                )),
+            })))
+        }
         _ => None,
     }
 }
@@ -0,0 +1,90 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{NullToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{Arc, LazyLock},
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["http#get"]);
+
+/// Splits an `http://host[:port]/path` URL into its address and request path.
+fn parse_url(url: &str) -> Option<(String, String, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+
+    if authority.is_empty() {
+        return None;
+    }
+
+    let host = authority.split(':').next()?.to_string();
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    Some((host, address, path.to_string()))
+}
+
+fn get(host: &str, address: &str, path: &str) -> Option<String> {
+    let mut stream = TcpStream::connect(address).ok()?;
+
+    // HTTP/1.0 keeps the response unchunked and closes the connection when done
+    let request = format!("GET {path} HTTP/1.0\r\nHost: {host}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).ok()?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).ok()?;
+
+    let response = String::from_utf8_lossy(&response);
+    let (_, body) = response.split_once("\r\n\r\n")?;
+
+    Some(body.to_string())
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "http#get" => {
+            if args.len() != 1 {
+                panic!("http#get requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let url = match value {
+                ValueToken::String(string) => string.value,
+                _ => panic!("http#get requires a string in {location}"),
+            };
+
+            let Some((host, address, path)) = parse_url(&url) else {
+                panic!("http#get only supports http:// URLs in {location}");
+            };
+
+            match get(&host, &address, &path) {
+                Some(body) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: body,
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        _ => None,
+    }
+}
@@ -2,14 +2,37 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{BaseToken, NullToken, ValueToken},
+        base::{BaseToken, NullToken, StringToken, ValueToken},
         logic::ExpressionToken,
     },
 };
 
-use std::sync::{Arc, LazyLock};
+use super::string;
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["io#println", "io#inspect"]);
+use std::{
+    io::{IsTerminal, Read},
+    process::{Command, Stdio},
+    sync::{Arc, LazyLock},
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "io#println",
+        "io#inspect",
+        "io#printf",
+        "io#printfln",
+        "io#read_all",
+        "io#read_password",
+    ]
+});
+
+fn write(runtime: &Runtime, text: &str) {
+    let output = runtime.output();
+    let mut output = output.lock().unwrap();
+
+    output.write_all(text.as_bytes()).unwrap();
+    output.flush().unwrap();
+}
 
 pub fn run(
     name: &str,
@@ -24,7 +47,7 @@ pub fn run(
             }
 
             let value = runtime.extract_value(&args[0])?;
-            println!("{}", value.value(0));
+            write(runtime, &format!("{}\n", value.value(0)));
 
             Some(ExpressionToken::Value(ValueToken::Null(NullToken {
                 location: Default::default(),
@@ -36,12 +59,97 @@ pub fn run(
             }
 
             let value = runtime.extract_value(&args[0])?;
-            println!("{} {}", value.location(), value.inspect());
+            write(
+                runtime,
+                &format!("{} {}\n", value.location(), value.inspect()),
+            );
 
             Some(ExpressionToken::Value(ValueToken::Null(NullToken {
                 location: Default::default(),
             })))
         }
+        "io#printf" => {
+            if args.is_empty() {
+                panic!("io#printf requires at least 1 argument in {location}");
+            }
+
+            let text = string::format(args, runtime)?;
+            write(runtime, &text);
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "io#printfln" => {
+            if args.is_empty() {
+                panic!("io#printfln requires at least 1 argument in {location}");
+            }
+
+            let text = string::format(args, runtime)?;
+            write(runtime, &format!("{text}\n"));
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "io#read_all" => {
+            if !args.is_empty() {
+                panic!("io#read_all requires no arguments in {location}");
+            }
+
+            let mut input = String::new();
+            match std::io::stdin().read_to_string(&mut input) {
+                Ok(_) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: input,
+                }))),
+                Err(_) => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "io#read_password" => {
+            if !args.is_empty() {
+                panic!("io#read_password requires no arguments in {location}");
+            }
+
+            let stdin = std::io::stdin();
+
+            // echo is toggled through stty so piped input still reads normally, and the
+            // sandbox spawns no processes, so there the input stays visible
+            let hide_echo = stdin.is_terminal() && !runtime.sandboxed();
+            if hide_echo {
+                Command::new("stty")
+                    .arg("-echo")
+                    .stdin(Stdio::inherit())
+                    .status()
+                    .ok();
+            }
+
+            let mut input = String::new();
+            let result = stdin.read_line(&mut input);
+
+            if hide_echo {
+                Command::new("stty")
+                    .arg("echo")
+                    .stdin(Stdio::inherit())
+                    .status()
+                    .ok();
+                write(runtime, "\n");
+            }
+
+            match result {
+                Ok(read) if read > 0 => {
+                    Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                        location: Default::default(),
+                        value: input.trim_end_matches(['\r', '\n']).to_string(),
+                    })))
+                }
+                _ => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
         _ => None,
     }
 }
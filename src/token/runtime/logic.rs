@@ -2,15 +2,234 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{BaseToken, BooleanToken, ValueToken},
-        logic::ExpressionToken,
+        base::{
+            ArrayToken, BaseToken, BooleanToken, BufferToken, ClassInstanceToken, FunctionToken,
+            NullToken, NumberToken, RangeToken, StringToken, ValueToken,
+        },
+        logic::{ExpressionToken, LetToken},
     },
 };
 
-use std::sync::{Arc, LazyLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "#eq",
+        "#lt",
+        "#gt",
+        "#and",
+        "#or",
+        "#assert",
+        "#assert_eq",
+        "#panic",
+        "#debug",
+        "#to_bool",
+        "#breakpoint",
+        "#deep_eq",
+        "#swap",
+        "#dump_scope",
+        "#defined",
+        "#pipe",
+        "#memoize",
+        "#clone",
+        "#arity",
+    ]
+});
+
+/// Structurally compares two values, descending into arrays and class instance fields.
+fn deep_eq(left: &ValueToken, right: &ValueToken, runtime: &mut Runtime) -> bool {
+    match (left, right) {
+        (ValueToken::Array(left), ValueToken::Array(right)) => {
+            let left = left.value.read().unwrap().clone();
+            let right = right.value.read().unwrap().clone();
+
+            if left.len() != right.len() {
+                return false;
+            }
+
+            for (left, right) in left.iter().zip(right.iter()) {
+                match (runtime.extract_value(left), runtime.extract_value(right)) {
+                    (Some(left), Some(right)) if deep_eq(&left, &right, runtime) => {}
+                    _ => return false,
+                }
+            }
+
+            true
+        }
+        (ValueToken::ClassInstance(left), ValueToken::ClassInstance(right)) => {
+            if left.class.read().unwrap().name != right.class.read().unwrap().name {
+                return false;
+            }
+
+            let left = super::class::fields(left);
+            let right = super::class::fields(right);
+
+            if left.len() != right.len() {
+                return false;
+            }
+
+            for ((left_key, left), (right_key, right)) in left.iter().zip(right.iter()) {
+                if left_key != right_key {
+                    return false;
+                }
+
+                match (runtime.extract_value(left), runtime.extract_value(right)) {
+                    (Some(left), Some(right)) if deep_eq(&left, &right, runtime) => {}
+                    _ => return false,
+                }
+            }
+
+            true
+        }
+        _ => left == right,
+    }
+}
+
+/// Copies a value so the copy shares no arrays, buffers, ranges or instance fields with
+/// the original. Functions, classes, namespaces and native memory stay shared. `cloned`
+/// maps already copied containers by address, so cycles and shared values survive.
+fn deep_clone(
+    value: &ValueToken,
+    runtime: &mut Runtime,
+    cloned: &mut HashMap<usize, ValueToken>,
+) -> ValueToken {
+    let clone_expression =
+        |expression: &ExpressionToken,
+         runtime: &mut Runtime,
+         cloned: &mut HashMap<usize, ValueToken>| match runtime.extract_value(expression) {
+            Some(value) => ExpressionToken::Value(deep_clone(&value, runtime, cloned)),
+            None => expression.clone(),
+        };
+
+    let address = match value {
+        ValueToken::Array(array) => Arc::as_ptr(&array.value) as *const () as usize,
+        ValueToken::Buffer(buffer) => Arc::as_ptr(&buffer.value) as *const () as usize,
+        ValueToken::Range(range) => Arc::as_ptr(&range.start) as *const () as usize,
+        ValueToken::ClassInstance(instance) => Arc::as_ptr(&instance.scope) as *const () as usize,
+        _ => return value.clone(),
+    };
+
+    if let Some(copy) = cloned.get(&address) {
+        return copy.clone();
+    }
+
+    match value {
+        ValueToken::Array(array) => {
+            let items = Arc::new(RwLock::new(Vec::new()));
+            cloned.insert(
+                address,
+                ValueToken::Array(ArrayToken {
+                    value: Arc::clone(&items),
+                    location: array.location.clone(),
+                }),
+            );
+
+            let source = array.value.read().unwrap().clone();
+            for item in source.iter() {
+                let item = clone_expression(item, runtime, cloned);
+                items.write().unwrap().push(item);
+            }
+        }
+        ValueToken::Buffer(buffer) => {
+            cloned.insert(
+                address,
+                ValueToken::Buffer(BufferToken {
+                    value: Arc::new(RwLock::new(buffer.value.read().unwrap().clone())),
+                    location: buffer.location.clone(),
+                }),
+            );
+        }
+        ValueToken::Range(range) => {
+            let start = range.start.read().unwrap().clone();
+            let end = range.end.read().unwrap().clone();
+
+            let copy = ValueToken::Range(RangeToken {
+                start: Arc::new(RwLock::new(clone_expression(&start, runtime, cloned))),
+                end: Arc::new(RwLock::new(clone_expression(&end, runtime, cloned))),
+                location: range.location.clone(),
+            });
+            cloned.insert(address, copy);
+        }
+        ValueToken::ClassInstance(instance) => {
+            let scope = Arc::new(RwLock::new(HashMap::new()));
+            cloned.insert(
+                address,
+                ValueToken::ClassInstance(ClassInstanceToken {
+                    class: Arc::clone(&instance.class),
+                    scope: Arc::clone(&scope),
+                    location: instance.location.clone(),
+                }),
+            );
+
+            let source = instance.scope.read().unwrap().clone();
+            for (key, value) in source {
+                let value = value.read().unwrap().clone();
+                let value = clone_expression(&value, runtime, cloned);
+
+                scope
+                    .write()
+                    .unwrap()
+                    .insert(key, Arc::new(RwLock::new(value)));
+            }
+        }
+        _ => unreachable!(),
+    }
+
+    cloned[&address].clone()
+}
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> =
-    LazyLock::new(|| vec!["#eq", "#lt", "#gt", "#and", "#or"]);
+/// Renders a value with nested arrays and class instance fields expanded,
+/// indenting each level by two spaces.
+fn debug(value: &ValueToken, spaces: usize, runtime: &mut Runtime) -> String {
+    let indent = " ".repeat(spaces);
+
+    match value {
+        ValueToken::String(string) => format!("{:?}", string.value),
+        ValueToken::Array(array) => {
+            let array = array.value.read().unwrap().clone();
+            if array.is_empty() {
+                return "[]".to_string();
+            }
+
+            let mut result = "[\n".to_string();
+
+            for token in array.iter() {
+                if let Some(value_token) = runtime.extract_value(token) {
+                    result.push_str(&format!(
+                        "{indent}  {}\n",
+                        debug(&value_token, spaces + 2, runtime)
+                    ));
+                }
+            }
+
+            result + &indent + "]"
+        }
+        ValueToken::ClassInstance(class_instance) => {
+            let name = class_instance.class.read().unwrap().name.clone();
+            let fields = super::class::fields(class_instance);
+            if fields.is_empty() {
+                return format!("{name} {{}}");
+            }
+
+            let mut result = format!("{name} {{\n");
+
+            for (key, token) in fields.iter() {
+                if let Some(value_token) = runtime.extract_value(token) {
+                    result.push_str(&format!(
+                        "{indent}  {key}: {}\n",
+                        debug(&value_token, spaces + 2, runtime)
+                    ));
+                }
+            }
+
+            result + &indent + "}"
+        }
+        _ => value.value(0),
+    }
+}
 
 pub fn run(
     name: &str,
@@ -114,6 +333,247 @@ pub fn run(
                 value: false,
             })))
         }
+        "#assert" => {
+            if args.len() != 1 {
+                panic!("#assert requires 1 argument on line {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+
+            if !value.truthy() {
+                eprintln!("{location}: assertion failed: {}", value.value(0));
+                std::process::exit(1);
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "#assert_eq" => {
+            if args.len() != 2 {
+                panic!("#assert_eq requires 2 arguments on line {location}");
+            }
+
+            let left = runtime.extract_value(&args[0])?;
+            let right = runtime.extract_value(&args[1])?;
+
+            if left.value(0) != right.value(0) {
+                eprintln!(
+                    "{location}: assertion failed: left != right\n  left: {}\n right: {}",
+                    left.value(0),
+                    right.value(0)
+                );
+                std::process::exit(1);
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "#panic" => {
+            if args.len() != 1 {
+                panic!("#panic requires 1 argument on line {location}");
+            }
+
+            let message = runtime.extract_value(&args[0])?;
+
+            eprintln!("{location}: {}", message.value(0));
+            std::process::exit(1);
+        }
+        "#debug" => {
+            if args.len() != 1 {
+                panic!("#debug requires 1 argument on line {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: debug(&value, 0, runtime),
+            })))
+        }
+        "#to_bool" => {
+            if args.len() != 1 {
+                panic!("#to_bool requires 1 argument on line {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: value.truthy(),
+            })))
+        }
+        "#breakpoint" => {
+            if !args.is_empty() {
+                panic!("#breakpoint requires no arguments on line {location}");
+            }
+
+            runtime.breakpoint(location);
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "#deep_eq" => {
+            if args.len() != 2 {
+                panic!("#deep_eq requires 2 arguments on line {location}");
+            }
+
+            let left = runtime.extract_value(&args[0])?;
+            let right = runtime.extract_value(&args[1])?;
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: deep_eq(&left, &right, runtime),
+            })))
+        }
+        "#swap" => {
+            if args.len() != 2 {
+                panic!("#swap requires 2 arguments on line {location}");
+            }
+
+            let mut variables = Vec::new();
+            for arg in args.iter() {
+                let ExpressionToken::Let(LetToken { name, is_const, .. }) = &**arg else {
+                    panic!("#swap requires 2 variables on line {location}");
+                };
+
+                if *is_const {
+                    panic!("cannot swap const '{name}' in {location}");
+                }
+
+                let Some(variable) = runtime.lookup_variable(name) else {
+                    panic!("variable '{name}' not found in {location}");
+                };
+
+                variables.push(variable);
+            }
+
+            if !Arc::ptr_eq(&variables[0], &variables[1]) {
+                let mut left = variables[0].write().unwrap();
+                let mut right = variables[1].write().unwrap();
+
+                std::mem::swap(&mut *left, &mut *right);
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        "#dump_scope" => {
+            if !args.is_empty() {
+                panic!("#dump_scope requires no arguments on line {location}");
+            }
+
+            let mut variables = runtime
+                .scope_aggregate(true)
+                .into_iter()
+                .collect::<Vec<_>>();
+            variables.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+            let mut pairs = Vec::new();
+            for (name, value) in variables {
+                // variables locked by an enclosing call (e.g. the running function) are skipped
+                let Ok(value) = value.try_read().map(|value| value.clone()) else {
+                    continue;
+                };
+                let Some(value) = runtime.extract_value(&value) else {
+                    continue;
+                };
+
+                let pair = [name, value.inspect()].map(|value| {
+                    ExpressionToken::Value(ValueToken::String(StringToken {
+                        location: Default::default(),
+                        value,
+                    }))
+                });
+
+                pairs.push(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                    location: Default::default(),
+                    value: Arc::new(RwLock::new(pair.to_vec())),
+                })));
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(pairs)),
+            })))
+        }
+        "#defined" => {
+            if args.len() != 1 {
+                panic!("#defined requires 1 argument on line {location}");
+            }
+
+            let ValueToken::String(name) = runtime.extract_value(&args[0])? else {
+                panic!("#defined requires a string on line {location}");
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: runtime.lookup_variable(&name.value).is_some(),
+            })))
+        }
+        "#pipe" => {
+            if args.len() < 2 {
+                panic!("#pipe requires at least 2 arguments on line {location}");
+            }
+
+            let mut value = runtime.extract_value(&args[0])?;
+
+            for arg in args[1..].iter() {
+                let ValueToken::Function(function) = runtime.extract_value(arg)? else {
+                    panic!("#pipe requires a value followed by functions on line {location}");
+                };
+
+                value = super::invoke(runtime, &function, vec![value]);
+            }
+
+            Some(ExpressionToken::Value(value))
+        }
+        "#memoize" => {
+            if args.len() != 1 {
+                panic!("#memoize requires 1 argument on line {location}");
+            }
+
+            let ValueToken::Function(function) = runtime.extract_value(&args[0])? else {
+                panic!("#memoize requires a function on line {location}");
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Function(
+                FunctionToken {
+                    cache: Some(Arc::new(Mutex::new(HashMap::new()))),
+                    ..function
+                },
+            )))
+        }
+        "#clone" => {
+            if args.len() != 1 {
+                panic!("#clone requires 1 argument on line {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+
+            Some(ExpressionToken::Value(deep_clone(
+                &value,
+                runtime,
+                &mut HashMap::new(),
+            )))
+        }
+        "#arity" => {
+            if args.len() != 1 {
+                panic!("#arity requires 1 argument on line {location}");
+            }
+
+            let ValueToken::Function(function) = runtime.extract_value(&args[0])? else {
+                panic!("#arity requires a function on line {location}");
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value: function.args.len() as f64,
+            })))
+        }
         _ => None,
     }
 }
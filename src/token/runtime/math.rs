@@ -2,7 +2,7 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{BaseToken, NumberToken, ValueToken},
+        base::{BaseToken, BooleanToken, NumberToken, StringToken, ValueToken},
         logic::ExpressionToken,
     },
 };
@@ -20,9 +20,27 @@ pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
         "math#round",
         "math#sqrt",
         "math#mod",
+        "math#to_hex",
+        "math#to_binary",
+        "math#hypot",
+        "math#atan2",
+        "math#lerp",
+        "math#isnan",
+        "math#isinf",
+        "math#factorial",
+        "math#gcd",
+        "math#lcm",
     ]
 });
 
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
 pub fn run(
     name: &str,
     args: &[Arc<ExpressionToken>],
@@ -143,6 +161,169 @@ pub fn run(
                 value: value.rem_euclid(value_divisor),
             })))
         }
+        "math#factorial" => {
+            if args.len() != 1 {
+                panic!("math#factorial requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let value = match value {
+                ValueToken::Number(value) => value.value,
+                _ => panic!("math#factorial requires a number in {location}"),
+            };
+
+            if value < 0.0 || value.fract() != 0.0 {
+                panic!("math#factorial requires a non-negative integer in {location}");
+            }
+
+            // 171! and above no longer fit in a float
+            let result = if value > 170.0 {
+                f64::INFINITY
+            } else {
+                (2..=value as u64).fold(1.0, |product, n| product * n as f64)
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value: result,
+            })))
+        }
+        "math#gcd" | "math#lcm" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let first = runtime.extract_value(&args[0])?;
+            let first = match first {
+                ValueToken::Number(value) => (value.value as i64).unsigned_abs(),
+                _ => panic!("{name} requires 2 numbers in {location}"),
+            };
+
+            let second = runtime.extract_value(&args[1])?;
+            let second = match second {
+                ValueToken::Number(value) => (value.value as i64).unsigned_abs(),
+                _ => panic!("{name} requires 2 numbers in {location}"),
+            };
+
+            let divisor = gcd(first, second);
+            let value = if name == "math#gcd" {
+                divisor as f64
+            } else {
+                // the lcm with zero is zero, and the divisor is only zero for two zeros
+                first
+                    .checked_div(divisor)
+                    .map_or(0.0, |first| first as f64 * second as f64)
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value,
+            })))
+        }
+        "math#hypot" | "math#atan2" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let first = runtime.extract_value(&args[0])?;
+            let first = match first {
+                ValueToken::Number(value) => value.value,
+                _ => panic!("{name} requires 2 numbers in {location}"),
+            };
+
+            let second = runtime.extract_value(&args[1])?;
+            let second = match second {
+                ValueToken::Number(value) => value.value,
+                _ => panic!("{name} requires 2 numbers in {location}"),
+            };
+
+            // math#hypot takes (x, y), math#atan2 takes (y, x)
+            let value = if name == "math#hypot" {
+                first.hypot(second)
+            } else {
+                first.atan2(second)
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value,
+            })))
+        }
+        "math#lerp" => {
+            if args.len() != 3 {
+                panic!("math#lerp requires 3 arguments in {location}");
+            }
+
+            let mut values = [0.0; 3];
+            for (i, arg) in args.iter().enumerate() {
+                values[i] = match runtime.extract_value(arg)? {
+                    ValueToken::Number(value) => value.value,
+                    _ => panic!("math#lerp requires 3 numbers in {location}"),
+                };
+            }
+
+            let [a, b, t] = values;
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value: a + (b - a) * t,
+            })))
+        }
+        "math#isnan" | "math#isinf" => {
+            if args.len() != 1 {
+                panic!("{name} requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let value = match value {
+                ValueToken::Number(value) => value.value,
+                _ => panic!("{name} requires a number in {location}"),
+            };
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: if name == "math#isnan" {
+                    value.is_nan()
+                } else {
+                    value.is_infinite()
+                },
+            })))
+        }
+        "math#to_hex" | "math#to_binary" => {
+            if args.is_empty() || args.len() > 2 {
+                panic!("{name} requires at least 1 argument and at most 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let value = match value {
+                ValueToken::Number(value) => value.value as i64,
+                _ => panic!("{name} requires a number in {location}"),
+            };
+
+            let width = if args.len() == 2 {
+                match runtime.extract_value(&args[1])? {
+                    ValueToken::Number(width) => width.value as usize,
+                    _ => panic!("{name} requires a number as the width in {location}"),
+                }
+            } else {
+                0
+            };
+
+            let digits = if name == "math#to_hex" {
+                format!("{:0width$x}", value.unsigned_abs())
+            } else {
+                format!("{:0width$b}", value.unsigned_abs())
+            };
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: if value < 0 {
+                    format!("-{digits}")
+                } else {
+                    digits
+                },
+            })))
+        }
         _ => None,
     }
 }
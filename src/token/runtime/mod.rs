@@ -1,16 +1,30 @@
 pub mod array;
+pub mod bits;
+pub mod buffer;
 pub mod class;
+pub mod config;
+pub mod csv;
+pub mod encoding;
 pub mod fs;
+pub mod hash;
+pub mod http;
 pub mod io;
 pub mod logic;
 pub mod math;
+pub mod regex;
 pub mod rng;
+pub mod set;
+pub mod signal;
 pub mod string;
 pub mod tcp;
 pub mod thread;
 pub mod time;
 
-use super::{TokenLocation, logic::ExpressionToken};
+use super::{
+    TokenLocation,
+    base::{FunctionToken, NullToken, NumberToken, ValueToken},
+    logic::ExpressionToken,
+};
 use crate::runtime::Runtime;
 
 use std::sync::{Arc, LazyLock};
@@ -29,16 +43,82 @@ pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
     vec.extend(&*tcp::FUNCTIONS);
     vec.extend(&*thread::FUNCTIONS);
     vec.extend(&*class::FUNCTIONS);
+    vec.extend(&*hash::FUNCTIONS);
+    vec.extend(&*encoding::FUNCTIONS);
+    vec.extend(&*regex::FUNCTIONS);
+    vec.extend(&*buffer::FUNCTIONS);
+    vec.extend(&*http::FUNCTIONS);
+    vec.extend(&*set::FUNCTIONS);
+    vec.extend(&*bits::FUNCTIONS);
+    vec.extend(&*signal::FUNCTIONS);
+    vec.extend(&*csv::FUNCTIONS);
+    vec.extend(&*config::FUNCTIONS);
 
     vec
 });
 
+pub fn extract_bytes(value: ValueToken, name: &str, location: &TokenLocation) -> Vec<u8> {
+    match value {
+        ValueToken::String(string) => string.value.into_bytes(),
+        ValueToken::Buffer(buffer) => buffer.value.read().unwrap().clone(),
+        _ => panic!("{name} requires a string or buffer in {location}"),
+    }
+}
+
+/// Converts a number to an index. Fractions are truncated, while negative, NaN and
+/// too large numbers have no index, instead of saturating like an `as` cast.
+pub fn to_index(number: &NumberToken) -> Option<usize> {
+    if number.value.is_nan() || number.value < 0.0 || number.value >= usize::MAX as f64 {
+        return None;
+    }
+
+    Some(number.value as usize)
+}
+
+/// Calls `function` with `args` and returns its result, or null without a return.
+pub fn invoke(
+    runtime: &mut Runtime,
+    function: &FunctionToken,
+    args: Vec<ValueToken>,
+) -> ValueToken {
+    match runtime.call_function(function, args) {
+        Some(ExpressionToken::Return(return_token)) => runtime
+            .extract_value(&return_token.value)
+            .unwrap_or(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })),
+        _ => ValueToken::Null(NullToken {
+            location: Default::default(),
+        }),
+    }
+}
+
 pub fn run(
     name: &str,
     args: &[Arc<ExpressionToken>],
     runtime: &mut Runtime,
     location: &TokenLocation,
 ) -> Option<ExpressionToken> {
+    if let Some(function) = runtime.host_function(name) {
+        let args = args
+            .iter()
+            .map(|arg| runtime.extract_value(arg))
+            .collect::<Option<Vec<_>>>()?;
+
+        return Some(ExpressionToken::Value(function(&args)));
+    }
+
+    if runtime.sandboxed()
+        && (fs::FUNCTIONS.contains(&name)
+            || tcp::FUNCTIONS.contains(&name)
+            || http::FUNCTIONS.contains(&name)
+            || thread::FUNCTIONS.contains(&name)
+            || signal::FUNCTIONS.contains(&name))
+    {
+        eprintln!("{location}: {name} is disabled in sandbox mode");
+        std::process::exit(1);
+    }
+
     if io::FUNCTIONS.contains(&name) {
         io::run(name, args, runtime, location)
     } else if string::FUNCTIONS.contains(&name) {
@@ -61,7 +141,27 @@ pub fn run(
         thread::run(name, args, runtime, location)
     } else if class::FUNCTIONS.contains(&name) {
         class::run(name, args, runtime, location)
+    } else if hash::FUNCTIONS.contains(&name) {
+        hash::run(name, args, runtime, location)
+    } else if encoding::FUNCTIONS.contains(&name) {
+        encoding::run(name, args, runtime, location)
+    } else if regex::FUNCTIONS.contains(&name) {
+        regex::run(name, args, runtime, location)
+    } else if buffer::FUNCTIONS.contains(&name) {
+        buffer::run(name, args, runtime, location)
+    } else if http::FUNCTIONS.contains(&name) {
+        http::run(name, args, runtime, location)
+    } else if set::FUNCTIONS.contains(&name) {
+        set::run(name, args, runtime, location)
+    } else if bits::FUNCTIONS.contains(&name) {
+        bits::run(name, args, runtime, location)
+    } else if signal::FUNCTIONS.contains(&name) {
+        signal::run(name, args, runtime, location)
+    } else if csv::FUNCTIONS.contains(&name) {
+        csv::run(name, args, runtime, location)
+    } else if config::FUNCTIONS.contains(&name) {
+        config::run(name, args, runtime, location)
     } else {
-        None
+        panic!("unknown function '{name}' in {location}");
     }
 }
@@ -0,0 +1,167 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{ArrayToken, BaseToken, BooleanToken, NullToken, StringToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "regex#is_match",
+        "regex#find",
+        "regex#find_all",
+        "regex#captures",
+        "regex#replace_all",
+    ]
+});
+
+static CACHE: LazyLock<Mutex<HashMap<String, Regex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn compile(pattern: &str, name: &str, location: &TokenLocation) -> Regex {
+    let mut cache = CACHE.lock().unwrap();
+
+    if let Some(regex) = cache.get(pattern) {
+        return regex.clone();
+    }
+
+    let regex = Regex::new(pattern)
+        .unwrap_or_else(|err| panic!("{name} received an invalid pattern in {location}: {err}"));
+    cache.insert(pattern.to_string(), regex.clone());
+
+    regex
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "regex#is_match" => {
+            if args.len() != 2 {
+                panic!("regex#is_match requires 2 arguments in {location}");
+            }
+
+            let pattern = runtime.extract_value(&args[0])?.value(0);
+            let subject = runtime.extract_value(&args[1])?.value(0);
+
+            let regex = compile(&pattern, name, location);
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: regex.is_match(&subject),
+            })))
+        }
+        "regex#find" => {
+            if args.len() != 2 {
+                panic!("regex#find requires 2 arguments in {location}");
+            }
+
+            let pattern = runtime.extract_value(&args[0])?.value(0);
+            let subject = runtime.extract_value(&args[1])?.value(0);
+
+            let regex = compile(&pattern, name, location);
+
+            match regex.find(&subject) {
+                Some(found) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: found.as_str().to_string(),
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "regex#find_all" => {
+            if args.len() != 2 {
+                panic!("regex#find_all requires 2 arguments in {location}");
+            }
+
+            let pattern = runtime.extract_value(&args[0])?.value(0);
+            let subject = runtime.extract_value(&args[1])?.value(0);
+
+            let regex = compile(&pattern, name, location);
+            let matches = regex
+                .find_iter(&subject)
+                .map(|found| {
+                    ExpressionToken::Value(ValueToken::String(StringToken {
+                        location: Default::default(),
+                        value: found.as_str().to_string(),
+                    }))
+                })
+                .collect();
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(matches)),
+            })))
+        }
+        "regex#captures" => {
+            if args.len() != 2 {
+                panic!("regex#captures requires 2 arguments in {location}");
+            }
+
+            let pattern = runtime.extract_value(&args[0])?.value(0);
+            let subject = runtime.extract_value(&args[1])?.value(0);
+
+            let regex = compile(&pattern, name, location);
+
+            // the whole match comes first, groups that didn't participate are null
+            match regex.captures(&subject) {
+                Some(captures) => {
+                    let groups = captures
+                        .iter()
+                        .map(|group| match group {
+                            Some(group) => {
+                                ExpressionToken::Value(ValueToken::String(StringToken {
+                                    location: Default::default(),
+                                    value: group.as_str().to_string(),
+                                }))
+                            }
+                            None => ExpressionToken::Value(ValueToken::Null(NullToken {
+                                location: Default::default(),
+                            })),
+                        })
+                        .collect();
+
+                    Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                        location: Default::default(),
+                        value: Arc::new(RwLock::new(groups)),
+                    })))
+                }
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "regex#replace_all" => {
+            if args.len() != 3 {
+                panic!("regex#replace_all requires 3 arguments in {location}");
+            }
+
+            let pattern = runtime.extract_value(&args[0])?.value(0);
+            let subject = runtime.extract_value(&args[1])?.value(0);
+            let replacement = runtime.extract_value(&args[2])?.value(0);
+
+            let regex = compile(&pattern, name, location);
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: regex
+                    .replace_all(&subject, replacement.as_str())
+                    .to_string(),
+            })))
+        }
+        _ => None,
+    }
+}
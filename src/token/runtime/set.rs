@@ -0,0 +1,133 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{ArrayToken, BaseToken, BooleanToken, NativeMemoryToken, NumberToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
+    vec![
+        "set#new",
+        "set#add",
+        "set#has",
+        "set#remove",
+        "set#size",
+        "set#to_array",
+    ]
+});
+
+/// Members keyed on their `value(0)` representation, ordered by that key.
+type Members = BTreeMap<String, ValueToken>;
+
+fn with_members<T>(
+    value: ValueToken,
+    name: &str,
+    location: &TokenLocation,
+    f: impl FnOnce(&mut Members) -> T,
+) -> T {
+    match value {
+        ValueToken::NativeMemory(memory) if memory.name == "Set" => {
+            let mut memory = memory.memory.lock().unwrap();
+            let members = memory.downcast_mut::<Members>().unwrap();
+
+            f(members)
+        }
+        _ => panic!("{name} requires a Set as the first argument in {location}"),
+    }
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "set#new" => {
+            if args.len() > 1 {
+                panic!("set#new requires at most 1 argument in {location}");
+            }
+
+            let mut members = Members::new();
+
+            if let Some(arg) = args.first() {
+                let ValueToken::Array(array) = runtime.extract_value(arg)? else {
+                    panic!("set#new requires an array in {location}");
+                };
+
+                let items = array.value.read().unwrap().clone();
+                for item in items.iter() {
+                    let value = runtime.extract_value(item)?;
+                    members.insert(value.value(0), value);
+                }
+            }
+
+            Some(ExpressionToken::Value(ValueToken::NativeMemory(
+                NativeMemoryToken {
+                    name: "Set".to_string(),
+                    memory: Arc::new(Mutex::new(Box::new(members))),
+                },
+            )))
+        }
+        "set#add" | "set#has" | "set#remove" => {
+            if args.len() != 2 {
+                panic!("{name} requires 2 arguments in {location}");
+            }
+
+            let set = runtime.extract_value(&args[0])?;
+            let value = runtime.extract_value(&args[1])?;
+            let key = value.value(0);
+
+            let result = with_members(set, name, location, |members| match name {
+                "set#add" => members.insert(key, value).is_none(),
+                "set#has" => members.contains_key(&key),
+                _ => members.remove(&key).is_some(),
+            });
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: result,
+            })))
+        }
+        "set#size" => {
+            if args.len() != 1 {
+                panic!("set#size requires 1 argument in {location}");
+            }
+
+            let set = runtime.extract_value(&args[0])?;
+            let size = with_members(set, name, location, |members| members.len());
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value: size as f64,
+            })))
+        }
+        "set#to_array" => {
+            if args.len() != 1 {
+                panic!("set#to_array requires 1 argument in {location}");
+            }
+
+            let set = runtime.extract_value(&args[0])?;
+            let values = with_members(set, name, location, |members| {
+                members
+                    .values()
+                    .cloned()
+                    .map(ExpressionToken::Value)
+                    .collect::<Vec<_>>()
+            });
+
+            Some(ExpressionToken::Value(ValueToken::Array(ArrayToken {
+                location: Default::default(),
+                value: Arc::new(RwLock::new(values)),
+            })))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    runtime::Runtime,
+    token::{
+        TokenLocation,
+        base::{NullToken, ValueToken},
+        logic::ExpressionToken,
+    },
+};
+
+use std::{
+    sync::{
+        Arc, LazyLock, Mutex, Once,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["signal#on_interrupt"]);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static HANDLER: Mutex<Option<Runtime>> = Mutex::new(None);
+static INSTALL: Once = Once::new();
+
+#[cfg(unix)]
+extern "C" fn interrupt(_: i32) {
+    // only async-signal-safe work here, the watcher thread does the rest
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+const SIGINT: i32 = 2;
+
+#[cfg(unix)]
+unsafe extern "C" {
+    fn signal(signum: i32, handler: usize) -> usize;
+}
+
+#[cfg(unix)]
+fn install(_: &TokenLocation) {
+    unsafe {
+        signal(SIGINT, interrupt as extern "C" fn(i32) as usize);
+    }
+}
+
+/// Puts back `SIG_DFL`, so another Ctrl-C kills the process outright.
+#[cfg(unix)]
+fn restore() {
+    const SIG_DFL: usize = 0;
+
+    unsafe {
+        signal(SIGINT, SIG_DFL);
+    }
+}
+
+#[cfg(not(unix))]
+fn install(location: &TokenLocation) {
+    panic!("signal#on_interrupt is only supported on unix in {location}");
+}
+
+#[cfg(not(unix))]
+fn restore() {}
+
+/// Runs the registered handler once an interrupt arrives, then exits. This happens
+/// on its own thread so it also works while the main thread is blocked, e.g. in
+/// `tcp#getconn`.
+fn watch() {
+    loop {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            restore();
+
+            // errors are panics, and a failing handler must still end the process
+            let handler = HANDLER.lock().unwrap().take();
+            if let Some(mut handler) = handler {
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| handler.run()));
+            }
+
+            std::process::exit(130);
+        }
+
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+pub fn run(
+    name: &str,
+    args: &[Arc<ExpressionToken>],
+    runtime: &mut Runtime,
+    location: &TokenLocation,
+) -> Option<ExpressionToken> {
+    match name {
+        "signal#on_interrupt" => {
+            if args.len() != 1 {
+                panic!("signal#on_interrupt requires 1 argument in {location}");
+            }
+
+            let ValueToken::Function(function) = runtime.extract_value(&args[0])? else {
+                panic!("signal#on_interrupt requires a function in {location}");
+            };
+
+            let handler = super::thread::function_runtime(runtime, function, Vec::new());
+            *HANDLER.lock().unwrap() = Some(handler);
+
+            INSTALL.call_once(|| {
+                install(location);
+                std::thread::spawn(watch);
+            });
+
+            Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                location: Default::default(),
+            })))
+        }
+        _ => None,
+    }
+}
@@ -2,11 +2,15 @@ use crate::{
     runtime::Runtime,
     token::{
         TokenLocation,
-        base::{ArrayToken, BaseToken, BooleanToken, NumberToken, StringToken, ValueToken},
+        base::{
+            ArrayToken, BaseToken, BooleanToken, NullToken, NumberToken, StringToken, ValueToken,
+        },
         logic::ExpressionToken,
     },
 };
 
+use super::array;
+
 use std::sync::{Arc, LazyLock, RwLock};
 
 pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
@@ -26,9 +30,118 @@ pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
         "string#starts_with",
         "string#ends_with",
         "string#contains",
+        "string#to_fixed",
+        "string#ord",
+        "string#chr",
+        "string#join",
+        "string#contains_any",
+        "string#starts_with_any",
+        "string#inspect",
+        "string#parse_int",
     ]
 });
 
+fn parse_int(value: &str, radix: Option<u32>) -> Option<f64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+
+    let (digits, radix) = match radix {
+        Some(radix) => (value, radix),
+        None => match value.get(..2) {
+            Some("0x" | "0X") => (&value[2..], 16),
+            Some("0b" | "0B") => (&value[2..], 2),
+            Some("0o" | "0O") => (&value[2..], 8),
+            _ => (value, 10),
+        },
+    };
+
+    // from_str_radix would also accept a second sign after the prefix
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+
+    let number = i64::from_str_radix(digits, radix).ok()? as f64;
+
+    Some(if negative { -number } else { number })
+}
+
+/// Applies a placeholder specifier such as `:>10`, `:<10`, `:^10` or `:05` to `value`.
+/// Returns `None` for anything it doesn't recognize.
+fn apply_spec(spec: &str, value: &str) -> Option<String> {
+    let spec = spec.strip_prefix(':')?;
+
+    let (align, spec) = match spec.chars().next() {
+        Some(align @ ('<' | '>' | '^')) => (Some(align), &spec[1..]),
+        _ => (None, spec),
+    };
+    let (zero, spec) = match spec.strip_prefix('0') {
+        Some(rest) if align.is_none() => (true, rest),
+        _ => (false, spec),
+    };
+
+    if spec.is_empty() || !spec.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let width = spec.parse::<usize>().ok()?;
+    let padding = width.saturating_sub(value.chars().count());
+
+    Some(match align {
+        _ if zero => match value.strip_prefix('-') {
+            Some(digits) => format!("-{}{digits}", "0".repeat(padding)),
+            None => format!("{}{value}", "0".repeat(padding)),
+        },
+        Some('>') => format!("{}{value}", " ".repeat(padding)),
+        Some('^') => format!(
+            "{}{value}{}",
+            " ".repeat(padding / 2),
+            " ".repeat(padding - padding / 2)
+        ),
+        _ => format!("{value}{}", " ".repeat(padding)),
+    })
+}
+
+pub fn format(args: &[Arc<ExpressionToken>], runtime: &mut Runtime) -> Option<String> {
+    let template = runtime.extract_value(&args[0])?.value(0).to_string();
+    let mut args = args.iter().skip(1);
+
+    let mut result = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let spec = &rest[1..end];
+        let formatted = if spec.is_empty() || apply_spec(spec, "").is_some() {
+            match args.next() {
+                Some(arg) => {
+                    let value = runtime.extract_value(arg)?.value(0);
+                    apply_spec(spec, &value).or(Some(value))
+                }
+                None => None,
+            }
+        } else {
+            None
+        };
+
+        // unrecognized specifiers and placeholders without an argument stay literal
+        result.push_str(formatted.as_deref().unwrap_or(&rest[..=end]));
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Some(result)
+}
+
 pub fn run(
     name: &str,
     args: &[Arc<ExpressionToken>],
@@ -59,23 +172,9 @@ pub fn run(
                 panic!("string#format requires at least 1 argument in {location}");
             }
 
-            let format = runtime.extract_value(&args[0])?.value(0).to_string();
-            let mut values = Vec::new();
-
-            for arg in args.iter().skip(1) {
-                let value = runtime.extract_value(arg)?;
-
-                values.push(value.value(0));
-            }
-
-            let mut result = format;
-            for value in values {
-                result = result.replacen("{}", &value, 1);
-            }
-
             Some(ExpressionToken::Value(ValueToken::String(StringToken {
                 location: Default::default(),
-                value: result,
+                value: format(args, runtime)?,
             })))
         }
         "string#len" => {
@@ -92,12 +191,24 @@ pub fn run(
             })))
         }
         "string#split" => {
-            if args.len() != 2 {
-                panic!("string#split requires 2 arguments in {location}");
+            if args.len() < 2 || args.len() > 3 {
+                panic!(
+                    "string#split requires at least 2 arguments and at most 3 arguments in {location}"
+                );
             }
 
             let value = runtime.extract_value(&args[0])?;
             let separator = runtime.extract_value(&args[1])?;
+            let limit = if args.len() == 3 {
+                match runtime.extract_value(&args[2])? {
+                    ValueToken::Number(limit) => limit.value as usize,
+                    _ => {
+                        panic!("string#split requires a number as the last argument in {location}")
+                    }
+                }
+            } else {
+                usize::MAX
+            };
 
             let value = value.value(0);
             let separator = separator.value(0);
@@ -106,7 +217,7 @@ pub fn run(
                 location: Default::default(),
                 value: Arc::new(RwLock::new(
                     value
-                        .split(&separator)
+                        .splitn(limit, &separator)
                         .map(|s| {
                             ExpressionToken::Value(ValueToken::String(StringToken {
                                 location: Default::default(),
@@ -216,12 +327,20 @@ pub fn run(
             let value = value.value(0);
             match (start, end) {
                 (ValueToken::Number(start), ValueToken::Number(end)) => {
-                    let start = start.value as usize;
-                    let end = end.value as usize;
+                    let (Some(start), Some(end)) = (super::to_index(&start), super::to_index(&end))
+                    else {
+                        return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                            location: Default::default(),
+                        })));
+                    };
 
+                    let end = end.min(value.len());
+                    let start = start.min(end);
+
+                    // reversed, out of bounds or mid-character ranges give an empty string
                     Some(ExpressionToken::Value(ValueToken::String(StringToken {
                         location: Default::default(),
-                        value: value[start..end].to_string(),
+                        value: value.get(start..end).unwrap_or_default().to_string(),
                     })))
                 }
                 _ => {
@@ -303,6 +422,155 @@ pub fn run(
                 value: value.contains(&search),
             })))
         }
+        "string#to_fixed" => {
+            if args.len() != 2 {
+                panic!("string#to_fixed requires 2 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let decimals = runtime.extract_value(&args[1])?;
+
+            match (value, decimals) {
+                (ValueToken::Number(value), ValueToken::Number(decimals)) => {
+                    Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                        location: Default::default(),
+                        value: format!("{:.*}", decimals.value as usize, value.value),
+                    })))
+                }
+                _ => {
+                    panic!("string#to_fixed requires 2 numbers in {location}")
+                }
+            }
+        }
+        "string#ord" => {
+            if args.len() != 1 {
+                panic!("string#ord requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let value = value.value(0);
+
+            match value.chars().next() {
+                Some(c) => Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                    location: Default::default(),
+                    value: c as u32 as f64,
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "string#chr" => {
+            if args.len() != 1 {
+                panic!("string#chr requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+            let value = match value {
+                ValueToken::Number(value) => value.value as u32,
+                _ => panic!("string#chr requires a number in {location}"),
+            };
+
+            match char::from_u32(value) {
+                Some(c) => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: c.to_string(),
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
+        "string#join" => Some(ExpressionToken::Value(ValueToken::String(StringToken {
+            location: Default::default(),
+            value: array::join(name, args, runtime, location)?,
+        }))),
+        "string#contains_any" => {
+            if args.len() < 3 {
+                panic!("string#contains_any requires at least 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?.value(0);
+
+            for arg in args.iter().skip(1) {
+                let search = runtime.extract_value(arg)?.value(0);
+
+                if value.contains(&search) {
+                    return Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                        location: Default::default(),
+                        value: true,
+                    })));
+                }
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: false,
+            })))
+        }
+        "string#starts_with_any" => {
+            if args.len() < 3 {
+                panic!("string#starts_with_any requires at least 3 arguments in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?.value(0);
+
+            for arg in args.iter().skip(1) {
+                let search = runtime.extract_value(arg)?.value(0);
+
+                if value.starts_with(&search) {
+                    return Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                        location: Default::default(),
+                        value: true,
+                    })));
+                }
+            }
+
+            Some(ExpressionToken::Value(ValueToken::Boolean(BooleanToken {
+                location: Default::default(),
+                value: false,
+            })))
+        }
+        "string#inspect" => {
+            if args.len() != 1 {
+                panic!("string#inspect requires 1 argument in {location}");
+            }
+
+            let value = runtime.extract_value(&args[0])?;
+
+            Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                location: Default::default(),
+                value: value.inspect(),
+            })))
+        }
+        "string#parse_int" => {
+            if args.is_empty() || args.len() > 2 {
+                panic!(
+                    "string#parse_int requires at least 1 argument and at most 2 arguments in {location}"
+                );
+            }
+
+            let value = runtime.extract_value(&args[0])?.value(0);
+            let radix = match args.get(1) {
+                Some(radix) => match runtime.extract_value(radix)? {
+                    ValueToken::Number(radix) if (2.0..=36.0).contains(&radix.value) => {
+                        Some(radix.value as u32)
+                    }
+                    _ => panic!("string#parse_int requires a radix between 2 and 36 in {location}"),
+                },
+                None => None,
+            };
+
+            match parse_int(&value, radix) {
+                Some(value) => Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                    location: Default::default(),
+                    value,
+                }))),
+                None => Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                    location: Default::default(),
+                }))),
+            }
+        }
         _ => None,
     }
 }
@@ -8,7 +8,7 @@ use crate::{
 };
 
 use std::{
-    io::{Read, Write},
+    io::{BufRead, BufReader, Read, Write},
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
@@ -20,6 +20,9 @@ pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| {
         "tcp#readbin",
         "tcp#write",
         "tcp#close",
+        "tcp#read_until",
+        "tcp#buf_reader",
+        "tcp#read_line",
     ]
 });
 
@@ -190,6 +193,117 @@ pub fn run(
                 panic!("tcp#write requires a TcpStream in {location}");
             }
         }
+        "tcp#read_until" => {
+            if args.len() != 2 {
+                panic!("tcp#read_until requires 2 arguments in {location}");
+            }
+
+            let reader = runtime.extract_value(&args[0]);
+            let delimiter = match runtime.extract_value(&args[1])? {
+                ValueToken::String(delimiter) if !delimiter.value.is_empty() => delimiter.value,
+                _ => panic!("tcp#read_until requires a non-empty string delimiter in {location}"),
+            };
+
+            // reading through the buffered reader keeps bytes past the delimiter for the
+            // next read, which a buffer local to this call would lose
+            if let Some(ValueToken::NativeMemory(reader)) = reader
+                && reader.name == "TcpBufReader"
+            {
+                let mut reader = reader.memory.lock().unwrap();
+                let reader = reader
+                    .as_mut()
+                    .downcast_mut::<BufReader<std::net::TcpStream>>()
+                    .unwrap();
+
+                let delimiter = delimiter.as_bytes();
+                let last = delimiter[delimiter.len() - 1];
+
+                let mut data = Vec::new();
+                while !data.ends_with(delimiter) {
+                    match reader.read_until(last, &mut data) {
+                        Ok(0) => break,
+                        Ok(_) => {}
+                        Err(err) => panic!("tcp#read_until failed in {location}: {err}"),
+                    }
+                }
+
+                if data.is_empty() {
+                    return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                        location: Default::default(),
+                    })));
+                }
+
+                if data.ends_with(delimiter) {
+                    data.truncate(data.len() - delimiter.len());
+                }
+
+                Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: String::from_utf8_lossy(&data).to_string(),
+                })))
+            } else {
+                panic!("tcp#read_until requires a TcpBufReader (see tcp#buf_reader) in {location}");
+            }
+        }
+        "tcp#buf_reader" => {
+            if args.len() != 1 {
+                panic!("tcp#buf_reader requires 1 argument in {location}");
+            }
+
+            let stream = runtime.extract_value(&args[0]);
+            if let Some(ValueToken::NativeMemory(stream)) = stream {
+                let stream = stream.memory.lock().unwrap();
+                let stream = stream
+                    .as_ref()
+                    .downcast_ref::<std::net::TcpStream>()
+                    .unwrap_or_else(|| panic!("tcp#buf_reader requires a TcpStream in {location}"));
+
+                // the reader owns a handle to the same socket, so buffered bytes
+                // are only visible through the reader from here on
+                let reader = BufReader::new(stream.try_clone().unwrap());
+
+                Some(ExpressionToken::Value(ValueToken::NativeMemory(
+                    NativeMemoryToken {
+                        name: "TcpBufReader".to_string(),
+                        memory: Arc::new(Mutex::new(Box::new(reader))),
+                    },
+                )))
+            } else {
+                panic!("tcp#buf_reader requires a TcpStream in {location}");
+            }
+        }
+        "tcp#read_line" => {
+            if args.len() != 1 {
+                panic!("tcp#read_line requires 1 argument in {location}");
+            }
+
+            let reader = runtime.extract_value(&args[0]);
+            if let Some(ValueToken::NativeMemory(reader)) = reader
+                && reader.name == "TcpBufReader"
+            {
+                let mut reader = reader.memory.lock().unwrap();
+                let reader = reader
+                    .as_mut()
+                    .downcast_mut::<BufReader<std::net::TcpStream>>()
+                    .unwrap();
+
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    return Some(ExpressionToken::Value(ValueToken::Null(NullToken {
+                        location: Default::default(),
+                    })));
+                }
+
+                let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+                Some(ExpressionToken::Value(ValueToken::String(StringToken {
+                    location: Default::default(),
+                    value: line.to_string(),
+                })))
+            } else {
+                panic!("tcp#read_line requires a TcpBufReader in {location}");
+            }
+        }
         _ => None,
     }
 }
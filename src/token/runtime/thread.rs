@@ -2,14 +2,78 @@ use crate::{
     runtime::Runtime,
     token::{
         Token, TokenLocation,
-        base::{NativeMemoryToken, NullToken, ValueToken},
+        base::{FunctionToken, NativeMemoryToken, NullToken, ValueToken},
         logic::{ExpressionToken, FnCallToken, LetToken},
     },
 };
 
 use std::sync::{Arc, LazyLock, Mutex, RwLock};
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["thread#launch", "thread#join"]);
+pub static FUNCTIONS: LazyLock<Vec<&str>> =
+    LazyLock::new(|| vec!["thread#launch", "thread#join", "#timeout"]);
+
+/// Builds a separate runtime that calls `function` with `args`, seeing the variables
+/// and host functions visible to `runtime`.
+pub fn function_runtime(
+    runtime: &mut Runtime,
+    function: FunctionToken,
+    args: Vec<ValueToken>,
+) -> Runtime {
+    let scope = runtime.scope_aggregate(true);
+    let mut tokens = Vec::new();
+
+    for (key, var_value) in scope.iter() {
+        if key == "main" {
+            continue;
+        }
+
+        let value = var_value.read().unwrap();
+
+        tokens.push(Token::Let(LetToken {
+            name: key.clone(),
+            is_const: false,
+            is_function: matches!(
+                runtime.extract_value(&value).unwrap(),
+                ValueToken::Function(_)
+            ),
+            is_class: matches!(runtime.extract_value(&value).unwrap(), ValueToken::Class(_)),
+            value: Arc::clone(var_value),
+
+            location: Default::default(),
+        }));
+    }
+
+    tokens.push(Token::Let(LetToken {
+        name: "main".to_string(),
+        is_const: true,
+        is_function: true,
+        is_class: false,
+        value: Arc::new(RwLock::new(ExpressionToken::Value(ValueToken::Function(
+            function,
+        )))),
+
+        location: Default::default(),
+    }));
+
+    tokens.push(Token::FnCall(FnCallToken {
+        name: "main".to_string(),
+        args: args
+            .into_iter()
+            .map(|arg| Arc::new(ExpressionToken::Value(arg)))
+            .collect(),
+        location: Default::default(),
+    }));
+
+    let mut function_runtime = Runtime::new(tokens);
+    function_runtime.set_output(runtime.output());
+    function_runtime.set_sandbox(runtime.sandboxed());
+    function_runtime.set_max_steps(runtime.max_steps());
+    for (name, function) in runtime.host_functions() {
+        function_runtime.register_fn(&name, move |args| function(args));
+    }
+
+    function_runtime
+}
 
 pub fn run(
     name: &str,
@@ -26,61 +90,14 @@ pub fn run(
             let function = runtime.extract_value(&args[0])?;
             match function {
                 ValueToken::Function(fn_token) => {
-                    let function = fn_token.clone();
                     let args: Vec<_> = args[1..]
                         .iter()
                         .map(|arg| runtime.extract_value(arg).unwrap())
                         .collect();
 
-                    let scope = runtime.scope_aggregate(true);
-                    let mut var_tokens = Vec::new();
-
-                    for (key, var_value) in scope.iter() {
-                        let value = var_value.read().unwrap();
-
-                        var_tokens.push(Token::Let(LetToken {
-                            name: key.clone(),
-                            is_const: false,
-                            is_function: matches!(
-                                runtime.extract_value(&value).unwrap(),
-                                ValueToken::Function(_)
-                            ),
-                            is_class: matches!(
-                                runtime.extract_value(&value).unwrap(),
-                                ValueToken::Class(_)
-                            ),
-                            value: Arc::clone(var_value),
-                        }));
-                    }
-
+                    let mut thread_runtime = function_runtime(runtime, fn_token, args);
                     let thread = std::thread::spawn(move || {
-                        let mut tokens = Vec::new();
-
-                        for variable in var_tokens {
-                            tokens.push(variable);
-                        }
-
-                        tokens.push(Token::Let(LetToken {
-                            name: "main".to_string(),
-                            is_const: true,
-                            is_function: true,
-                            is_class: false,
-                            value: Arc::new(RwLock::new(ExpressionToken::Value(
-                                ValueToken::Function(function),
-                            ))),
-                        }));
-
-                        tokens.push(Token::FnCall(FnCallToken {
-                            name: "main".to_string(),
-                            args: args
-                                .iter()
-                                .map(|arg| Arc::new(ExpressionToken::Value(arg.clone())))
-                                .collect(),
-                            location: Default::default(),
-                        }));
-
-                        let mut runtime = Runtime::new(tokens);
-                        runtime.run();
+                        thread_runtime.run();
                     });
 
                     Some(ExpressionToken::Value(ValueToken::NativeMemory(
@@ -121,6 +138,42 @@ pub fn run(
                 panic!("thread#kill requires a Thread in {location}");
             }
         }
+        "#timeout" => {
+            if args.len() != 2 {
+                panic!("#timeout requires 2 arguments in {location}");
+            }
+
+            let milliseconds = runtime.extract_value(&args[0])?;
+            let function = runtime.extract_value(&args[1])?;
+            match (milliseconds, function) {
+                (ValueToken::Number(milliseconds), ValueToken::Function(fn_token)) => {
+                    let Some(milliseconds) = super::to_index(&milliseconds) else {
+                        panic!("#timeout requires a non-negative timeout in {location}");
+                    };
+
+                    let mut thread_runtime = function_runtime(runtime, fn_token, Vec::new());
+                    let (sender, receiver) = std::sync::mpsc::channel();
+
+                    // a function that times out keeps running detached, its result discarded
+                    std::thread::spawn(move || {
+                        let _ = sender.send(thread_runtime.run());
+                    });
+
+                    let result = receiver
+                        .recv_timeout(std::time::Duration::from_millis(milliseconds as u64))
+                        .ok()
+                        .flatten()
+                        .unwrap_or(ValueToken::Null(NullToken {
+                            location: Default::default(),
+                        }));
+
+                    Some(ExpressionToken::Value(result))
+                }
+                _ => {
+                    panic!("#timeout requires a number and a function in {location}");
+                }
+            }
+        }
         _ => None,
     }
 }
@@ -9,7 +9,8 @@ use crate::{
 
 use std::sync::{Arc, LazyLock};
 
-pub static FUNCTIONS: LazyLock<Vec<&str>> = LazyLock::new(|| vec!["time#sleep", "time#now"]);
+pub static FUNCTIONS: LazyLock<Vec<&str>> =
+    LazyLock::new(|| vec!["time#sleep", "time#now", "time#measure"]);
 
 pub fn run(
     name: &str,
@@ -49,6 +50,29 @@ pub fn run(
                 value: unix_time as f64 / 1000.0,
             })))
         }
+        "time#measure" => {
+            if args.is_empty() {
+                panic!("time#measure requires at least 1 argument in {location}");
+            }
+
+            let function = runtime.extract_value(&args[0])?;
+            let ValueToken::Function(fn_token) = function else {
+                panic!("time#measure requires a function in {location}");
+            };
+
+            let args = args[1..]
+                .iter()
+                .map(|arg| runtime.extract_value(arg).unwrap())
+                .collect();
+
+            let start = std::time::Instant::now();
+            runtime.call_function(&fn_token, args);
+
+            Some(ExpressionToken::Value(ValueToken::Number(NumberToken {
+                location: Default::default(),
+                value: start.elapsed().as_secs_f64() * 1000.0,
+            })))
+        }
         _ => None,
     }
 }